@@ -1,26 +1,50 @@
-#![cfg_attr(not(feature = "std-entry"), no_std)]
-#![cfg_attr(not(feature = "std-entry"), no_main)]
+#![cfg_attr(not(any(feature = "std-entry", test)), no_std)]
+#![cfg_attr(not(any(feature = "std-entry", test)), no_main)]
 
-#[cfg(not(feature = "std-entry"))]
+#[cfg(not(any(feature = "std-entry", test)))]
 openvm::entry!(main);
 
-#[cfg(not(feature = "std-entry"))]
+#[cfg(not(any(feature = "std-entry", test)))]
 fn main() {
     use openvm::io::{read, reveal_u32};
-    use xmss_types::VerificationBatch;
+    use xmss_types::{VerificationBatch, REVEAL_LAYOUT as LAYOUT};
 
     let batch: VerificationBatch = read();
 
+    #[cfg(not(feature = "profiling"))]
     let (all_valid, count) = xmss_verify::verify_batch(&batch);
-    reveal_u32(all_valid as u32, 0);
-    reveal_u32(count as u32, 1);
-    // Reveal 256-bit statement commitment at indices 2..=9 (LE u32 words)
+    #[cfg(feature = "profiling")]
+    let ((all_valid, count), hash_calls) = xmss_verify::verify_batch_profiled(&batch);
+    reveal_u32(all_valid as u32, LAYOUT.all_valid);
+    reveal_u32(count as u32, LAYOUT.count);
+    // Reveal 256-bit statement commitment (LE u32 words)
     let h = xmss_verify::statement_commitment(&batch.statement);
-    for (i, chunk) in h.chunks(4).enumerate() {
-        let mut w = [0u8; 4];
-        w.copy_from_slice(chunk);
-        reveal_u32(u32::from_le_bytes(w), ((2 + i) as u32).try_into().unwrap());
+    for (i, w) in xmss_verify::digest_words_le(&h).into_iter().enumerate() {
+        reveal_u32(w, LAYOUT.commitment + i);
+    }
+    // Reveal number of distinct signer roots
+    reveal_u32(
+        xmss_verify::distinct_signers(&batch.statement),
+        LAYOUT.distinct_signers,
+    );
+    // Reveal the verified signature slice [start, end) so range proofs chain
+    let (start, end) = batch.statement.range.unwrap_or((0, batch.statement.k));
+    reveal_u32(start, LAYOUT.range_start);
+    reveal_u32(end, LAYOUT.range_end);
+    // Reveal the accepted epoch window [min, max] (each as low, high word)
+    let (min_epoch, max_epoch) = batch.statement.epoch_window.unwrap_or((0, u64::MAX));
+    for (i, w) in [min_epoch, max_epoch]
+        .into_iter()
+        .flat_map(|epoch| [epoch as u32, (epoch >> 32) as u32])
+        .enumerate()
+    {
+        reveal_u32(w, LAYOUT.epoch_window + i);
     }
+    // Reveal the witness bytes processed, so a truncated witness is detectable
+    reveal_u32(batch.witness.revealed_bytes(), LAYOUT.witness_bytes);
+    // Profiling builds also reveal the Poseidon hash-call count after the default words
+    #[cfg(feature = "profiling")]
+    reveal_u32(hash_calls, LAYOUT.hash_calls);
 }
 
 #[cfg(feature = "std-entry")]
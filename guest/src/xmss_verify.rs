@@ -9,18 +9,22 @@ use p3_koala_bear::{
     KoalaBear, Poseidon2KoalaBear, default_koalabear_poseidon2_16, default_koalabear_poseidon2_24,
 };
 use p3_symmetric::Permutation;
-use xmss_types::{PublicKey, Signature, Statement, TslParams, VerificationBatch};
+use xmss_types::{PoseidonConfig, PublicKey, Signature, Statement, TslParams, VerificationBatch};
 
-const FE_BYTES: usize = core::mem::size_of::<KoalaBear>();
-const HASH_LEN_FE: usize = 7;
-const PARAMETER_LEN_FE: usize = 5;
-const RANDOMNESS_LEN_FE: usize = 5;
+// Array widths are compile-time here, so they come from the config the host exports with.
+const POSEIDON: PoseidonConfig = PoseidonConfig::DEFAULT;
+const FE_BYTES: usize = PoseidonConfig::FE_BYTES;
+const HASH_LEN_FE: usize = POSEIDON.hash_len_fe;
+const PARAMETER_LEN_FE: usize = POSEIDON.parameter_len_fe;
+const RANDOMNESS_LEN_FE: usize = POSEIDON.randomness_len_fe;
 const TWEAK_LEN_FE: usize = 2;
 const MSG_LEN_FE: usize = 9;
 const NUM_CHUNKS_MESSAGE: usize = 155;
 const NUM_CHUNKS_CHECKSUM: usize = 8;
 const NUM_CHAINS: usize = NUM_CHUNKS_MESSAGE + NUM_CHUNKS_CHECKSUM;
 const TREE_HEIGHT: usize = 18;
+// Longest forest path `verify_one_hypertree` accepts, enough to address 2^32 subtrees.
+const MAX_FOREST_HEIGHT: usize = 32;
 const BASE: usize = 2;
 const FIELD_MODULUS: u32 = KoalaBear::ORDER_U64 as u32;
 const TWEAK_SEPARATOR_FOR_MESSAGE_HASH: u8 = 0x02;
@@ -33,9 +37,46 @@ const POSEIDON_INPUT_PAIR: usize = PARAMETER_LEN_FE + TWEAK_LEN_FE + 2 * HASH_LE
 const MESSAGE_HASH_INPUT_LEN: usize =
     RANDOMNESS_LEN_FE + PARAMETER_LEN_FE + TWEAK_LEN_FE + MSG_LEN_FE;
 
+/// A Merkle tree node as KoalaBear field elements.
+pub type Node = [KoalaBear; HASH_LEN_FE];
+
+/// Why a batch or a single signature was rejected. The public entry points still report plain
+/// `bool`s; this keeps the reason available to the checks themselves and to tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+    /// `statement.range` is reversed or extends past `k`.
+    InvalidRange,
+    /// The statement's public keys do not fit `k` (see [`bound_public_keys`]).
+    PublicKeyCountMismatch,
+    /// The witness does not hold exactly one signature per verified index.
+    SignatureCountMismatch,
+    /// The parameters are not the instantiation this guest verifies.
+    UnsupportedParams,
+    /// An authentication path does not cover the declared tree height.
+    TreeHeightMismatch,
+    /// The epoch does not fit in 32 bits.
+    EpochOutOfRange,
+    /// The epoch lies outside the statement's `epoch_window`.
+    EpochOutsideWindow,
+    /// The witness randomness does not hash to the statement's `randomness_commitment`.
+    RandomnessCommitmentMismatch,
+    /// A signature or public key field has the wrong length.
+    MalformedEntry,
+    /// The signature's leaf index is not the one its epoch signs with.
+    LeafIndexMismatch,
+    /// The message is not a 32-byte digest.
+    MalformedMessage,
+    /// The recomputed root is neither the key's root nor one of its acceptable roots.
+    RootMismatch,
+}
+
 struct PoseidonContext {
     perm16: Poseidon2KoalaBear<16>,
     perm24: Poseidon2KoalaBear<24>,
+    // Number of Poseidon hash calls, used as a proving-cost proxy since OpenVM exposes no
+    // cycle counter to guests.
+    #[cfg(feature = "profiling")]
+    hash_calls: core::cell::Cell<u32>,
 }
 
 impl PoseidonContext {
@@ -43,142 +84,627 @@ impl PoseidonContext {
         Self {
             perm16: default_koalabear_poseidon2_16(),
             perm24: default_koalabear_poseidon2_24(),
+            #[cfg(feature = "profiling")]
+            hash_calls: core::cell::Cell::new(0),
         }
     }
 
     fn perm16(&self) -> &Poseidon2KoalaBear<16> {
+        #[cfg(feature = "profiling")]
+        self.hash_calls.set(self.hash_calls.get().wrapping_add(1));
         &self.perm16
     }
 
     fn perm24(&self) -> &Poseidon2KoalaBear<24> {
+        #[cfg(feature = "profiling")]
+        self.hash_calls.set(self.hash_calls.get().wrapping_add(1));
         &self.perm24
     }
 }
 
+// Profiling builds enter through `verify_batch_profiled` instead.
+#[cfg_attr(all(feature = "profiling", not(test)), allow(dead_code))]
 pub fn verify_batch(batch: &VerificationBatch) -> (bool, u32) {
-    let expected = batch.statement.k as usize;
-    if batch.statement.public_keys.len() != expected
-        || batch.witness.signatures.len() != expected
-    {
-        return (false, 0);
-    }
+    verify_batch_with(batch, &PoseidonContext::new())
+}
 
-    if !params_match(&batch.params) {
-        return (false, 0);
-    }
+/// [`verify_batch`] plus the number of Poseidon hash calls it made.
+#[cfg(feature = "profiling")]
+pub fn verify_batch_profiled(batch: &VerificationBatch) -> ((bool, u32), u32) {
+    let poseidon = PoseidonContext::new();
+    let result = verify_batch_with(batch, &poseidon);
+    (result, poseidon.hash_calls.get())
+}
 
-    let epoch = match u32::try_from(batch.statement.ep) {
-        Ok(v) => v,
+fn verify_batch_with(batch: &VerificationBatch, poseidon: &PoseidonContext) -> (bool, u32) {
+    let (start, end, public_keys, epoch) = match check_batch(batch) {
+        Ok(checked) => checked,
         Err(_) => return (false, 0),
     };
 
-    let poseidon = PoseidonContext::new();
     let mut all_valid = true;
     let mut count: u32 = 0;
     for (sig, pk) in batch
         .witness
         .signatures
         .iter()
-        .zip(batch.statement.public_keys.iter())
+        .zip(public_keys[start..end].iter())
     {
-        let ok = verify_one(sig, pk, &batch.statement.m, epoch, &poseidon);
+        #[cfg(not(feature = "insecure-merkle-only"))]
+        let ok = verify_one(sig, pk, &batch.statement.m, epoch, poseidon).is_ok();
+        #[cfg(feature = "insecure-merkle-only")]
+        let ok = verify_merkle_only(sig, pk, epoch, poseidon).is_ok();
         all_valid &= ok;
         count += 1;
     }
     (all_valid, count)
 }
 
+/// Batch-wide checks made before any signature is verified. Returns the verified signature
+/// range, the bound public keys, and the epoch as a `u32`.
+fn check_batch(
+    batch: &VerificationBatch,
+) -> Result<(usize, usize, &[PublicKey], u32), VerifyError> {
+    let (start, end) = signature_range(&batch.statement).ok_or(VerifyError::InvalidRange)?;
+    let public_keys =
+        bound_public_keys(&batch.statement).ok_or(VerifyError::PublicKeyCountMismatch)?;
+    if batch.witness.signatures.len() != end - start {
+        return Err(VerifyError::SignatureCountMismatch);
+    }
+
+    if !params_match(&batch.params) {
+        return Err(VerifyError::UnsupportedParams);
+    }
+
+    // A signature whose authentication path disagrees with the declared tree height makes
+    // the whole batch malformed, which is reported separately from a per-signature failure.
+    let tree_height = batch.params.tree_height as usize;
+    if batch
+        .witness
+        .signatures
+        .iter()
+        .any(|sig| auth_path_height(sig) != tree_height)
+    {
+        return Err(VerifyError::TreeHeightMismatch);
+    }
+
+    if !epoch_in_window(&batch.statement) {
+        return Err(VerifyError::EpochOutsideWindow);
+    }
+    if let Some(expected) = batch.statement.randomness_commitment {
+        if randomness_commitment(&batch.witness.signatures) != expected {
+            return Err(VerifyError::RandomnessCommitmentMismatch);
+        }
+    }
+    let epoch = u32::try_from(batch.statement.ep).map_err(|_| VerifyError::EpochOutOfRange)?;
+    Ok((start, end, public_keys, epoch))
+}
+
+/// Version byte that starts the commitment preimage. Version 0 was the untagged layout, version 1
+/// had no epoch window, version 2 no randomness commitment, and version 3 no Ed25519 keys or
+/// hybrid flag; bump it whenever the preimage changes, together with the host mirror in
+/// `xmss_lib::reference`.
+const COMMITMENT_FORMAT_VERSION: u8 = 4;
+// Domain tag written before each statement field in the commitment preimage.
+const TAG_K: u8 = 0x01;
+const TAG_EP: u8 = 0x02;
+const TAG_M: u8 = 0x03;
+const TAG_PUBKEYS: u8 = 0x04;
+const TAG_RANGE: u8 = 0x05;
+const TAG_EPOCH_WINDOW: u8 = 0x06;
+const TAG_RANDOMNESS: u8 = 0x07;
+const TAG_HYBRID: u8 = 0x08;
+
 pub fn statement_commitment(stmt: &Statement) -> [u8; 32] {
     let mut buf = alloc::vec::Vec::new();
+    buf.push(COMMITMENT_FORMAT_VERSION);
+    buf.push(TAG_K);
     buf.extend_from_slice(&stmt.k.to_le_bytes());
+    buf.push(TAG_EP);
     buf.extend_from_slice(&stmt.ep.to_le_bytes());
+    buf.push(TAG_M);
     let mlen: u32 = stmt.m.len() as u32;
     buf.extend_from_slice(&mlen.to_le_bytes());
     buf.extend_from_slice(&stmt.m);
-    let pklen: u32 = stmt.public_keys.len() as u32;
+    buf.push(TAG_PUBKEYS);
+    let public_keys = bound_public_keys(stmt).unwrap_or(&stmt.public_keys);
+    let pklen: u32 = public_keys.len() as u32;
     buf.extend_from_slice(&pklen.to_le_bytes());
-    for pk in &stmt.public_keys {
+    for pk in public_keys {
         buf.extend_from_slice(&pk.root);
         buf.extend_from_slice(&pk.parameter);
+        let roots_len: u32 = pk.acceptable_roots.len() as u32;
+        buf.extend_from_slice(&roots_len.to_le_bytes());
+        for root in &pk.acceptable_roots {
+            buf.extend_from_slice(root);
+        }
+        match &pk.ed25519_key {
+            None => buf.push(0),
+            Some(key) => {
+                buf.push(1);
+                let key_len: u32 = key.len() as u32;
+                buf.extend_from_slice(&key_len.to_le_bytes());
+                buf.extend_from_slice(key);
+            }
+        }
+    }
+    buf.push(TAG_RANGE);
+    match stmt.range {
+        None => buf.push(0),
+        Some((start, end)) => {
+            buf.push(1);
+            buf.extend_from_slice(&start.to_le_bytes());
+            buf.extend_from_slice(&end.to_le_bytes());
+        }
+    }
+    buf.push(TAG_EPOCH_WINDOW);
+    match stmt.epoch_window {
+        None => buf.push(0),
+        Some((min_epoch, max_epoch)) => {
+            buf.push(1);
+            buf.extend_from_slice(&min_epoch.to_le_bytes());
+            buf.extend_from_slice(&max_epoch.to_le_bytes());
+        }
+    }
+    buf.push(TAG_RANDOMNESS);
+    match &stmt.randomness_commitment {
+        None => buf.push(0),
+        Some(commitment) => {
+            buf.push(1);
+            buf.extend_from_slice(commitment);
+        }
+    }
+    // The guest does not check Ed25519; the flag is committed so the host check is bound to it.
+    buf.push(TAG_HYBRID);
+    buf.push(stmt.hybrid_ed25519 as u8);
+    sha256(&buf)
+}
+
+/// SHA-256 over each signature's randomness in order, each prefixed by its length as a
+/// little-endian `u32`; the value `statement.randomness_commitment` must equal when set.
+pub fn randomness_commitment(signatures: &[Signature]) -> [u8; 32] {
+    let mut buf = Vec::new();
+    for sig in signatures {
+        buf.extend_from_slice(&(sig.randomness.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&sig.randomness);
     }
     sha256(&buf)
 }
 
+/// Split a digest into the `u32` words revealed as public output. Word `i` is bytes
+/// `4i..4i + 4` read little-endian; the host reassembles the digest with `to_le_bytes`, so both
+/// sides must change together.
+pub fn digest_words_le(digest: &[u8; 32]) -> [u32; 8] {
+    let mut words = [0u32; 8];
+    for (word, chunk) in words.iter_mut().zip(digest.chunks_exact(4)) {
+        *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    words
+}
+
+/// Signature slice `[start, end)` this statement covers: `statement.range` if set, otherwise all
+/// `k` signatures. `None` if the range is reversed or extends past `k`.
+pub fn signature_range(stmt: &Statement) -> Option<(usize, usize)> {
+    let (start, end) = stmt.range.unwrap_or((0, stmt.k));
+    if start > end || end > stmt.k {
+        return None;
+    }
+    Some((start as usize, end as usize))
+}
+
+/// Whether `stmt.ep` lies in the statement's inclusive `epoch_window`; always true without one.
+pub fn epoch_in_window(stmt: &Statement) -> bool {
+    match stmt.epoch_window {
+        Some((min_epoch, max_epoch)) => (min_epoch..=max_epoch).contains(&stmt.ep),
+        None => true,
+    }
+}
+
+/// Public keys the statement binds: all of them, or only the first `k` when
+/// `allow_extra_public_keys` is set. `None` if the key count does not fit the mode.
+pub fn bound_public_keys(stmt: &Statement) -> Option<&[PublicKey]> {
+    let k = stmt.k as usize;
+    let keys = &stmt.public_keys;
+    if keys.len() == k || (stmt.allow_extra_public_keys && keys.len() > k) {
+        Some(&keys[..k])
+    } else {
+        None
+    }
+}
+
+/// Number of distinct `pk.root` values among the statement's bound public keys. `no_std` has no
+/// hash set, so this is a linear scan over the keys seen so far.
+pub fn distinct_signers(stmt: &Statement) -> u32 {
+    let keys = bound_public_keys(stmt).unwrap_or(&stmt.public_keys);
+    keys.iter()
+        .enumerate()
+        .filter(|(i, pk)| keys[..*i].iter().all(|seen| seen.root != pk.root))
+        .count() as u32
+}
+
+/// Verify signature `index` of `statement` against the matching public key without building a
+/// full [`VerificationBatch`]. Applies the same parameter, length, and index checks as a batch.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn verify_single(
+    params: &TslParams,
+    statement: &Statement,
+    witness_sig: &Signature,
+    index: usize,
+) -> bool {
+    if !params_match(params) || auth_path_height(witness_sig) != params.tree_height as usize {
+        return false;
+    }
+    if index >= statement.k as usize {
+        return false;
+    }
+    let pk = match statement.public_keys.get(index) {
+        Some(pk) => pk,
+        None => return false,
+    };
+    let epoch = match u32::try_from(statement.ep) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+
+    let poseidon = PoseidonContext::new();
+    verify_one(witness_sig, pk, &statement.m, epoch, &poseidon).is_ok()
+}
+
+/// Leaf of a height-`tree_height` XMSS tree that signs epoch `ep`. Each epoch owns exactly one
+/// leaf, so this is the identity within the key's lifetime and `None` past its last epoch.
+pub fn leaf_index_for_epoch(ep: u64, tree_height: u16) -> Option<u64> {
+    let lifetime = 1u64.checked_shl(tree_height as u32)?;
+    (ep < lifetime).then_some(ep)
+}
+
 fn params_match(params: &TslParams) -> bool {
     params.w == 2
         && params.v as usize == NUM_CHAINS
         && params.tree_height as usize == TREE_HEIGHT
 }
 
+// `insecure-merkle-only` builds verify through `verify_merkle_only` instead.
+#[cfg_attr(feature = "insecure-merkle-only", allow(dead_code))]
 fn verify_one(
     sig: &Signature,
     pk: &PublicKey,
     message: &[u8],
     epoch: u32,
     poseidon: &PoseidonContext,
+) -> Result<(), VerifyError> {
+    let entry = decode_entry(sig, pk, epoch, poseidon)?;
+    let root = recompute_root(poseidon, &entry, message, epoch)?;
+    if entry.accepts(&root) {
+        Ok(())
+    } else {
+        Err(VerifyError::RootMismatch)
+    }
+}
+
+/// INSECURE, `insecure-merkle-only` builds only: take `sig.wots_chain_ends` as already-walked
+/// chain ends and check just the leaf hash and authentication path against `pk`. Nothing binds
+/// the signature to the message, so this exists solely to attribute proving cost between the
+/// WOTS chains and the Merkle path.
+#[cfg(feature = "insecure-merkle-only")]
+fn verify_merkle_only(
+    sig: &Signature,
+    pk: &PublicKey,
+    epoch: u32,
+    poseidon: &PoseidonContext,
+) -> Result<(), VerifyError> {
+    let entry = decode_entry(sig, pk, epoch, poseidon)?;
+    let root = hash_tree_root(
+        poseidon,
+        &entry.parameter,
+        epoch,
+        &entry.chain_hashes,
+        &entry.auth_path,
+    )
+    .ok_or(VerifyError::MalformedEntry)?;
+    if entry.accepts(&root) {
+        Ok(())
+    } else {
+        Err(VerifyError::RootMismatch)
+    }
+}
+
+/// Recompute the Merkle root that `sig` authenticates for `message` at epoch `ep`, without
+/// comparing it to `pk.root` or `pk.acceptable_roots`. `pk` still supplies the hashing parameter.
+///
+/// Returns `None` when the inputs are malformed; callers can compare the result against any
+/// number of candidate roots.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn compute_root(
+    params: &TslParams,
+    sig: &Signature,
+    message: &[u8],
+    ep: u64,
+    pk: &PublicKey,
+) -> Option<Node> {
+    if !params_match(params) {
+        return None;
+    }
+    let epoch = u32::try_from(ep).ok()?;
+    let poseidon = PoseidonContext::new();
+    let entry = decode_entry(sig, pk, epoch, &poseidon).ok()?;
+    recompute_root(&poseidon, &entry, message, epoch).ok()
+}
+
+/// Where a subtree sits in a multi-tree (hypertree) key: its index among the subtrees and the
+/// siblings from its root up to the forest root, one per level above the subtrees.
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct ForestPosition<'a> {
+    pub subtree_index: u32,
+    pub path: &'a [Vec<u8>],
+}
+
+/// Verify `sig` as a signature from the subtree at `position` of a multi-tree (hypertree) key
+/// whose top-level root is `forest_root`. The authentication path leads to the subtree root, and
+/// `position.path` continues from there to the forest root; forest levels use the tree tweak,
+/// numbered on from `TREE_HEIGHT`.
+///
+/// The forest path is not part of the batch wire format and no statement flag selects this
+/// check, so nothing the guest proves attests it. `pk` supplies the hashing parameter; its roots
+/// are not consulted. The forest path must be non-empty, at most `MAX_FOREST_HEIGHT` long, and
+/// long enough to address the subtree index.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn verify_one_hypertree(
+    params: &TslParams,
+    sig: &Signature,
+    message: &[u8],
+    ep: u64,
+    pk: &PublicKey,
+    position: &ForestPosition,
+    forest_root: &[u8],
+) -> bool {
+    let ForestPosition {
+        subtree_index,
+        path: forest_path,
+    } = *position;
+    let forest_height = forest_path.len();
+    if forest_height == 0
+        || forest_height > MAX_FOREST_HEIGHT
+        || (subtree_index as u64) >> forest_height != 0
+    {
+        return false;
+    }
+    let subtree_root = match compute_root(params, sig, message, ep, pk) {
+        Some(root) => root,
+        None => return false,
+    };
+    let (parameter, path, expected) = match (
+        bytes_to_field_array::<PARAMETER_LEN_FE>(&pk.parameter),
+        decode_domains(forest_path),
+        bytes_to_field_array::<HASH_LEN_FE>(forest_root),
+    ) {
+        (Some(parameter), Some(path), Some(expected)) => (parameter, path, expected),
+        _ => return false,
+    };
+    let poseidon = PoseidonContext::new();
+    fold_path(
+        &poseidon,
+        &parameter,
+        TREE_HEIGHT,
+        subtree_index,
+        &subtree_root,
+        &path,
+    ) == expected
+}
+
+fn recompute_root(
+    poseidon: &PoseidonContext,
+    entry: &DecodedEntry,
+    message: &[u8],
+    epoch: u32,
+) -> Result<Node, VerifyError> {
+    let digest = digest_to_array(message).ok_or(VerifyError::MalformedMessage)?;
+    let codeword = winternitz_codeword(
+        poseidon,
+        &entry.parameter,
+        epoch,
+        &entry.randomness,
+        &digest,
+    );
+    root_with_codeword(poseidon, entry, epoch, &codeword).ok_or(VerifyError::MalformedEntry)
+}
+
+/// Verify `sig` with chain positions supplied by the caller instead of deriving them from the
+/// message hash. `steps[i]` is the position at which chain `i` was revealed.
+///
+/// The message is never hashed on this path, so the caller is responsible for binding `steps`
+/// to the signed message; it exists to experiment with computing the encoding off-circuit.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn verify_one_with_steps(
+    params: &TslParams,
+    sig: &Signature,
+    steps: &[u16],
+    ep: u64,
+    pk: &PublicKey,
 ) -> bool {
-    if sig.wots_chain_ends.len() != NUM_CHAINS {
+    if !params_match(params) {
         return false;
     }
-    if sig.auth_path.len() != TREE_HEIGHT {
+    if steps.len() != NUM_CHAINS || steps.iter().any(|&s| s as usize >= BASE) {
         return false;
     }
+    let epoch = match u32::try_from(ep) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    let poseidon = PoseidonContext::new();
+    let entry = match decode_entry(sig, pk, epoch, &poseidon) {
+        Ok(e) => e,
+        Err(_) => return false,
+    };
+
+    let codeword: Vec<u8> = steps.iter().map(|&s| s as u8).collect();
+    verify_with_codeword(&poseidon, &entry, epoch, &codeword)
+}
+
+/// Field-element view of one signature and its public key, after all length checks.
+struct DecodedEntry {
+    randomness: [KoalaBear; RANDOMNESS_LEN_FE],
+    parameter: [KoalaBear; PARAMETER_LEN_FE],
+    pk_root: [KoalaBear; HASH_LEN_FE],
+    acceptable_roots: Vec<Node>,
+    chain_hashes: Vec<[KoalaBear; HASH_LEN_FE]>,
+    auth_path: Vec<[KoalaBear; HASH_LEN_FE]>,
+}
+
+impl DecodedEntry {
+    /// Whether `root` is the key's root or one of its additional acceptable roots.
+    fn accepts(&self, root: &Node) -> bool {
+        *root == self.pk_root || self.acceptable_roots.contains(root)
+    }
+}
+
+fn decode_entry(
+    sig: &Signature,
+    pk: &PublicKey,
+    epoch: u32,
+    poseidon: &PoseidonContext,
+) -> Result<DecodedEntry, VerifyError> {
+    use VerifyError::MalformedEntry;
+
+    if sig.wots_chain_ends.len() != NUM_CHAINS {
+        return Err(MalformedEntry);
+    }
+    if auth_path_height(sig) != TREE_HEIGHT {
+        return Err(VerifyError::TreeHeightMismatch);
+    }
+    // Every real signature carries fixed-size randomness; an empty or short field would
+    // otherwise only surface as a root mismatch after the full chain walk.
     if sig.randomness.len() != RANDOMNESS_LEN_FE * FE_BYTES {
-        return false;
+        return Err(MalformedEntry);
     }
     if pk.parameter.len() != PARAMETER_LEN_FE * FE_BYTES
         || pk.root.len() != HASH_LEN_FE * FE_BYTES
     {
-        return false;
+        return Err(MalformedEntry);
     }
-    if sig.leaf_index != epoch {
-        return false;
+    if leaf_index_for_epoch(epoch as u64, TREE_HEIGHT as u16) != Some(sig.leaf_index as u64) {
+        return Err(VerifyError::LeafIndexMismatch);
     }
 
-    let randomness = match bytes_to_field_array::<RANDOMNESS_LEN_FE>(&sig.randomness) {
-        Some(r) => r,
-        None => return false,
-    };
-    let parameter = match bytes_to_field_array::<PARAMETER_LEN_FE>(&pk.parameter) {
-        Some(p) => p,
-        None => return false,
-    };
-    let pk_root = match bytes_to_field_array::<HASH_LEN_FE>(&pk.root) {
-        Some(r) => r,
-        None => return false,
-    };
-    let chain_hashes = match decode_domains(&sig.wots_chain_ends) {
-        Some(v) => v,
-        None => return false,
-    };
-    let auth_path = match decode_domains(&sig.auth_path) {
-        Some(v) => v,
-        None => return false,
-    };
-    let digest = match digest_to_array(message) {
-        Some(d) => d,
-        None => return false,
+    let parameter =
+        bytes_to_field_array::<PARAMETER_LEN_FE>(&pk.parameter).ok_or(MalformedEntry)?;
+    Ok(DecodedEntry {
+        randomness: bytes_to_field_array::<RANDOMNESS_LEN_FE>(&sig.randomness)
+            .ok_or(MalformedEntry)?,
+        pk_root: bytes_to_field_array::<HASH_LEN_FE>(&pk.root).ok_or(MalformedEntry)?,
+        acceptable_roots: decode_domains(&pk.acceptable_roots).ok_or(MalformedEntry)?,
+        chain_hashes: decode_domains(&sig.wots_chain_ends).ok_or(MalformedEntry)?,
+        auth_path: expand_auth_path(sig, poseidon, &parameter).ok_or(MalformedEntry)?,
+        parameter,
+    })
+}
+
+/// Number of tree levels `sig` covers, counting siblings omitted through `auth_path_mask`.
+fn auth_path_height(sig: &Signature) -> usize {
+    match &sig.auth_path_mask {
+        Some(mask) => mask.len(),
+        None => sig.auth_path.len(),
+    }
+}
+
+/// Decode `sig.auth_path`, substituting [`default_node`] for every level the mask omits.
+fn expand_auth_path(
+    sig: &Signature,
+    poseidon: &PoseidonContext,
+    parameter: &[KoalaBear; PARAMETER_LEN_FE],
+) -> Option<Vec<[KoalaBear; HASH_LEN_FE]>> {
+    let mask = match &sig.auth_path_mask {
+        Some(mask) => mask,
+        None => return decode_domains(&sig.auth_path),
     };
+    if mask.iter().filter(|&&present| present).count() != sig.auth_path.len() {
+        return None;
+    }
+    let mut present_nodes = sig.auth_path.iter();
+    let mut out = Vec::with_capacity(mask.len());
+    // Default nodes are built bottom-up once, only as high as the omitted levels require.
+    let mut default = [KoalaBear::ZERO; HASH_LEN_FE];
+    let mut default_level = 0u32;
+    for (level, &present) in mask.iter().enumerate() {
+        if present {
+            out.push(bytes_to_field_array::<HASH_LEN_FE>(present_nodes.next()?)?);
+        } else {
+            while default_level < level as u32 {
+                default_level += 1;
+                default = empty_parent(poseidon, parameter, default_level, &default);
+            }
+            out.push(default);
+        }
+    }
+    Some(out)
+}
+
+/// Canonical root of an empty subtree of height `level`, used for omitted auth-path siblings.
+/// Level 0 is the all-zero node; each higher level hashes two copies of the level below with the
+/// tree tweak for `(level, 0)`, so the value depends only on `level` and the key's parameter.
+#[cfg_attr(not(test), allow(dead_code))]
+fn default_node(
+    poseidon: &PoseidonContext,
+    parameter: &[KoalaBear; PARAMETER_LEN_FE],
+    level: u32,
+) -> [KoalaBear; HASH_LEN_FE] {
+    let mut node = [KoalaBear::ZERO; HASH_LEN_FE];
+    for l in 1..=level {
+        node = empty_parent(poseidon, parameter, l, &node);
+    }
+    node
+}
 
-    let codeword = winternitz_codeword(poseidon, &parameter, epoch, &randomness, &digest);
+fn empty_parent(
+    poseidon: &PoseidonContext,
+    parameter: &[KoalaBear; PARAMETER_LEN_FE],
+    level: u32,
+    child: &[KoalaBear; HASH_LEN_FE],
+) -> [KoalaBear; HASH_LEN_FE] {
+    poseidon_apply(
+        poseidon,
+        parameter,
+        &PoseidonTweak::tree(level as u8, 0),
+        &[*child, *child],
+    )
+}
+
+fn verify_with_codeword(
+    poseidon: &PoseidonContext,
+    entry: &DecodedEntry,
+    epoch: u32,
+    codeword: &[u8],
+) -> bool {
+    match root_with_codeword(poseidon, entry, epoch, codeword) {
+        Some(root) => entry.accepts(&root),
+        None => false,
+    }
+}
+
+fn root_with_codeword(
+    poseidon: &PoseidonContext,
+    entry: &DecodedEntry,
+    epoch: u32,
+    codeword: &[u8],
+) -> Option<Node> {
     if codeword.len() != NUM_CHAINS {
-        return false;
+        return None;
     }
 
     let mut chain_ends = Vec::with_capacity(NUM_CHAINS);
     for (chain_index, (&steps_seen, start_hash)) in codeword
         .iter()
-        .zip(chain_hashes.iter())
+        .zip(entry.chain_hashes.iter())
         .enumerate()
     {
         let start_pos = steps_seen as u8;
         if steps_seen as usize >= BASE {
-            return false;
+            return None;
         }
         let remaining = (BASE - 1) as u8 - start_pos;
         let progressed = walk_chain(
             poseidon,
-            &parameter,
+            &entry.parameter,
             epoch,
             chain_index as u8,
             start_pos,
@@ -188,13 +714,12 @@ fn verify_one(
         chain_ends.push(progressed);
     }
 
-    hash_tree_verify(
+    hash_tree_root(
         poseidon,
-        &parameter,
-        &pk_root,
+        &entry.parameter,
         epoch,
         &chain_ends,
-        &auth_path,
+        &entry.auth_path,
     )
 }
 
@@ -334,18 +859,46 @@ fn walk_chain(
     current
 }
 
-fn hash_tree_verify(
+fn hash_tree_root(
     poseidon: &PoseidonContext,
     parameter: &[KoalaBear; PARAMETER_LEN_FE],
-    root: &[KoalaBear; HASH_LEN_FE],
     position: u32,
     leaf: &[[KoalaBear; HASH_LEN_FE]],
     path: &[[KoalaBear; HASH_LEN_FE]],
-) -> bool {
+) -> Option<Node> {
     if path.len() != TREE_HEIGHT {
-        return false;
+        return None;
     }
-    let mut current = poseidon_apply(poseidon, parameter, &PoseidonTweak::tree(0, position), leaf);
+    let leaf_node = poseidon_apply(poseidon, parameter, &PoseidonTweak::tree(0, position), leaf);
+    Some(merkle_root_from_path(
+        poseidon, parameter, position, &leaf_node, path,
+    ))
+}
+
+/// Fold `path` into the root above `leaf_node`. An empty path describes a single-leaf tree
+/// (height 0), whose root is the leaf itself. Verification never takes that shortcut:
+/// [`hash_tree_root`] requires a full `TREE_HEIGHT` path, so height-0 inputs such as the old dummy
+/// benchmark batch are rejected before their leaf could be compared with `pk.root`.
+fn merkle_root_from_path(
+    poseidon: &PoseidonContext,
+    parameter: &[KoalaBear; PARAMETER_LEN_FE],
+    position: u32,
+    leaf_node: &[KoalaBear; HASH_LEN_FE],
+    path: &[[KoalaBear; HASH_LEN_FE]],
+) -> [KoalaBear; HASH_LEN_FE] {
+    fold_path(poseidon, parameter, 0, position, leaf_node, path)
+}
+
+/// Hash `node`, sitting at `position` on level `base_level`, up through the siblings in `path`.
+fn fold_path(
+    poseidon: &PoseidonContext,
+    parameter: &[KoalaBear; PARAMETER_LEN_FE],
+    base_level: usize,
+    position: u32,
+    node: &[KoalaBear; HASH_LEN_FE],
+    path: &[[KoalaBear; HASH_LEN_FE]],
+) -> [KoalaBear; HASH_LEN_FE] {
+    let mut current = *node;
     let mut idx = position;
     for (level, sibling) in path.iter().enumerate() {
         let children = if idx & 1 == 0 {
@@ -357,11 +910,11 @@ fn hash_tree_verify(
         current = poseidon_apply(
             poseidon,
             parameter,
-            &PoseidonTweak::tree(level as u8 + 1, idx),
+            &PoseidonTweak::tree((base_level + level) as u8 + 1, idx),
             &children,
         );
     }
-    current == *root
+    current
 }
 
 #[derive(Copy, Clone)]
@@ -643,3 +1196,844 @@ impl SmallBigUint {
         rem as u32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xmss_types::Witness;
+
+    fn dummy_signature() -> Signature {
+        Signature {
+            leaf_index: 0,
+            randomness: vec![0u8; RANDOMNESS_LEN_FE * FE_BYTES],
+            wots_chain_ends: vec![vec![0u8; HASH_LEN_FE * FE_BYTES]; NUM_CHAINS],
+            auth_path: vec![vec![0u8; HASH_LEN_FE * FE_BYTES]; TREE_HEIGHT],
+            auth_path_mask: None,
+            ed25519_signature: None,
+        }
+    }
+
+    fn w1_params() -> TslParams {
+        TslParams {
+            w: BASE as u16,
+            v: NUM_CHAINS as u16,
+            d0: 0,
+            security_bits: 128,
+            tree_height: TREE_HEIGHT as u16,
+        }
+    }
+
+    fn dummy_batch(k: usize) -> VerificationBatch {
+        VerificationBatch {
+            params: w1_params(),
+            statement: Statement {
+                k: k as u32,
+                ep: 0,
+                m: vec![0u8; 32],
+                public_keys: vec![
+                    PublicKey {
+                        root: vec![0u8; HASH_LEN_FE * FE_BYTES],
+                        parameter: vec![0u8; PARAMETER_LEN_FE * FE_BYTES],
+                        acceptable_roots: Vec::new(),
+                        ed25519_key: None,
+                    };
+                    k
+                ],
+                range: None,
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+            },
+            witness: Witness {
+                signatures: vec![dummy_signature(); k],
+            },
+        }
+    }
+
+    /// Consecutive small field elements starting at `start`, encoded like exported nodes.
+    fn field_bytes(start: u32, len_fe: usize) -> Vec<u8> {
+        (0..len_fe as u32)
+            .flat_map(|i| (start + i).to_le_bytes())
+            .collect()
+    }
+
+    fn field_array_to_bytes(arr: &[KoalaBear]) -> Vec<u8> {
+        arr.iter()
+            .flat_map(|fe| (fe.as_canonical_u64() as u32).to_le_bytes())
+            .collect()
+    }
+
+    /// Arbitrary but well-formed signature material for `epoch`, distinct per `seed`.
+    fn synthetic_signature(seed: u32, epoch: u32) -> Signature {
+        let base = seed * 10_000;
+        Signature {
+            leaf_index: epoch,
+            randomness: field_bytes(base, RANDOMNESS_LEN_FE),
+            wots_chain_ends: (0..NUM_CHAINS as u32)
+                .map(|i| field_bytes(base + 100 + i * HASH_LEN_FE as u32, HASH_LEN_FE))
+                .collect(),
+            auth_path: (0..TREE_HEIGHT as u32)
+                .map(|i| field_bytes(base + 5_000 + i * HASH_LEN_FE as u32, HASH_LEN_FE))
+                .collect(),
+            auth_path_mask: None,
+            ed25519_signature: None,
+        }
+    }
+
+    fn synthetic_parameter(seed: u32) -> Vec<u8> {
+        field_bytes(seed * 10_000 + 9_000, PARAMETER_LEN_FE)
+    }
+
+    /// Derive the public key `sig` verifies under by running the verifier's chain walk and
+    /// Merkle hashing forward from the signature material.
+    fn synthetic_key(sig: &Signature, seed: u32, message: &[u8], epoch: u32) -> PublicKey {
+        let poseidon = PoseidonContext::new();
+        let parameter_bytes = synthetic_parameter(seed);
+        let parameter = bytes_to_field_array::<PARAMETER_LEN_FE>(&parameter_bytes).unwrap();
+        let randomness = bytes_to_field_array::<RANDOMNESS_LEN_FE>(&sig.randomness).unwrap();
+        let digest = digest_to_array(message).unwrap();
+        let starts = decode_domains(&sig.wots_chain_ends).unwrap();
+        let path = decode_domains(&sig.auth_path).unwrap();
+
+        let codeword = winternitz_codeword(&poseidon, &parameter, epoch, &randomness, &digest);
+        let chain_ends: Vec<_> = codeword
+            .iter()
+            .zip(starts.iter())
+            .enumerate()
+            .map(|(i, (&seen, start))| {
+                let remaining = BASE - 1 - seen as usize;
+                walk_chain(
+                    &poseidon, &parameter, epoch, i as u8, seen, remaining, start,
+                )
+            })
+            .collect();
+        let leaf_node = poseidon_apply(
+            &poseidon,
+            &parameter,
+            &PoseidonTweak::tree(0, epoch),
+            &chain_ends,
+        );
+        let root = merkle_root_from_path(&poseidon, &parameter, epoch, &leaf_node, &path);
+
+        PublicKey {
+            root: field_array_to_bytes(&root),
+            parameter: parameter_bytes,
+            acceptable_roots: Vec::new(),
+            ed25519_key: None,
+        }
+    }
+
+    #[test]
+    fn verify_single_checks_one_entry_of_statement() {
+        let message = [0x42u8; 32];
+        let epoch = 0;
+        let signatures: Vec<_> = (1..=3)
+            .map(|seed| synthetic_signature(seed, epoch))
+            .collect();
+        let statement = Statement {
+            k: 3,
+            ep: epoch as u64,
+            m: message.to_vec(),
+            public_keys: signatures
+                .iter()
+                .zip(1..=3)
+                .map(|(sig, seed)| synthetic_key(sig, seed, &message, epoch))
+                .collect(),
+            range: None,
+            allow_extra_public_keys: false,
+            epoch_window: None,
+            randomness_commitment: None,
+            hybrid_ed25519: false,
+        };
+
+        assert!(verify_single(&w1_params(), &statement, &signatures[1], 1));
+        assert!(!verify_single(&w1_params(), &statement, &signatures[1], 0));
+        assert!(!verify_single(&w1_params(), &statement, &signatures[1], 3));
+    }
+
+    #[test]
+    fn supplied_steps_match_recomputed_encoding() {
+        let message = [0x24u8; 32];
+        let epoch = 0;
+        let sig = synthetic_signature(7, epoch);
+        let pk = synthetic_key(&sig, 7, &message, epoch);
+        assert!(verify_one(&sig, &pk, &message, epoch, &PoseidonContext::new()).is_ok());
+
+        let parameter = bytes_to_field_array::<PARAMETER_LEN_FE>(&pk.parameter).unwrap();
+        let randomness = bytes_to_field_array::<RANDOMNESS_LEN_FE>(&sig.randomness).unwrap();
+        let codeword = winternitz_codeword(
+            &PoseidonContext::new(),
+            &parameter,
+            epoch,
+            &randomness,
+            &message,
+        );
+        let mut steps: Vec<u16> = codeword.iter().map(|&c| c as u16).collect();
+        assert!(verify_one_with_steps(&w1_params(), &sig, &steps, 0, &pk));
+
+        steps[0] ^= 1;
+        assert!(!verify_one_with_steps(&w1_params(), &sig, &steps, 0, &pk));
+        steps[0] = BASE as u16;
+        assert!(!verify_one_with_steps(&w1_params(), &sig, &steps, 0, &pk));
+        let truncated = &steps[1..];
+        assert!(!verify_one_with_steps(
+            &w1_params(),
+            &sig,
+            truncated,
+            0,
+            &pk
+        ));
+    }
+
+    #[test]
+    fn compressed_auth_path_reconstructs_same_root() {
+        let message = [0x11u8; 32];
+        let epoch = 0;
+        let omitted = [2usize, 5, 17];
+        let poseidon = PoseidonContext::new();
+        let parameter = bytes_to_field_array::<PARAMETER_LEN_FE>(&synthetic_parameter(4)).unwrap();
+        let mut full = synthetic_signature(4, epoch);
+        for &level in &omitted {
+            let node = default_node(&poseidon, &parameter, level as u32);
+            full.auth_path[level] = field_array_to_bytes(&node);
+        }
+        let pk = synthetic_key(&full, 4, &message, epoch);
+
+        let mut compressed = full.clone();
+        compressed.auth_path = full
+            .auth_path
+            .iter()
+            .enumerate()
+            .filter(|(level, _)| !omitted.contains(level))
+            .map(|(_, node)| node.clone())
+            .collect();
+        compressed.auth_path_mask = Some(
+            (0..TREE_HEIGHT)
+                .map(|level| !omitted.contains(&level))
+                .collect(),
+        );
+
+        assert!(verify_one(&full, &pk, &message, epoch, &poseidon).is_ok());
+        assert!(verify_one(&compressed, &pk, &message, epoch, &poseidon).is_ok());
+
+        let mut short_mask = compressed.clone();
+        short_mask.auth_path_mask.as_mut().unwrap().pop();
+        assert_eq!(
+            verify_one(&short_mask, &pk, &message, epoch, &poseidon),
+            Err(VerifyError::TreeHeightMismatch)
+        );
+    }
+
+    #[test]
+    fn widths_follow_the_shared_poseidon_config() {
+        assert_eq!(FE_BYTES, core::mem::size_of::<KoalaBear>());
+        assert_eq!(core::mem::size_of::<Node>(), POSEIDON.node_bytes());
+        assert_eq!(
+            core::mem::size_of::<[KoalaBear; PARAMETER_LEN_FE]>(),
+            POSEIDON.parameter_bytes()
+        );
+        assert_eq!(
+            core::mem::size_of::<[KoalaBear; RANDOMNESS_LEN_FE]>(),
+            POSEIDON.randomness_bytes()
+        );
+        let sig = synthetic_signature(1, 0);
+        let payload = sig.randomness.len()
+            + sig.wots_chain_ends.iter().map(Vec::len).sum::<usize>()
+            + sig.auth_path.iter().map(Vec::len).sum::<usize>();
+        assert_eq!(payload, POSEIDON.signature_bytes(NUM_CHAINS, TREE_HEIGHT));
+    }
+
+    #[test]
+    fn default_nodes_are_level_dependent_and_stable() {
+        let parameter = bytes_to_field_array::<PARAMETER_LEN_FE>(&synthetic_parameter(1)).unwrap();
+        let poseidon = PoseidonContext::new();
+        let nodes: Vec<_> = (0..5)
+            .map(|level| default_node(&poseidon, &parameter, level))
+            .collect();
+
+        assert_eq!(nodes[0], [KoalaBear::ZERO; HASH_LEN_FE]);
+        for (i, a) in nodes.iter().enumerate() {
+            for b in &nodes[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+        let fresh = PoseidonContext::new();
+        assert_eq!(default_node(&fresh, &parameter, 4), nodes[4]);
+    }
+
+    #[test]
+    fn hypertree_signature_verifies_against_forest_root() {
+        let message = [0x51u8; 32];
+        let epoch = 0;
+        let sig = synthetic_signature(7, epoch);
+        let pk = synthetic_key(&sig, 7, &message, epoch);
+        let poseidon = PoseidonContext::new();
+        let parameter = bytes_to_field_array::<PARAMETER_LEN_FE>(&pk.parameter).unwrap();
+        let subtree_root = bytes_to_field_array::<HASH_LEN_FE>(&pk.root).unwrap();
+        let sibling = bytes_to_field_array::<HASH_LEN_FE>(&field_bytes(77, HASH_LEN_FE)).unwrap();
+        // Two subtrees: the signer's tree is subtree 1, so its root is the right child.
+        let forest_root = field_array_to_bytes(&poseidon_apply(
+            &poseidon,
+            &parameter,
+            &PoseidonTweak::tree(TREE_HEIGHT as u8 + 1, 0),
+            &[sibling, subtree_root],
+        ));
+        let forest_path = vec![field_array_to_bytes(&sibling)];
+
+        let verify = |forest_path: &[Vec<u8>], subtree_index: u32, message: &[u8]| {
+            verify_one_hypertree(
+                &w1_params(),
+                &sig,
+                message,
+                epoch as u64,
+                &pk,
+                &ForestPosition {
+                    subtree_index,
+                    path: forest_path,
+                },
+                &forest_root,
+            )
+        };
+        assert!(verify(&forest_path, 1, &message[..]));
+        assert!(!verify(&forest_path, 0, &message[..]));
+        assert!(!verify(&forest_path, 2, &message[..]));
+        assert!(!verify(&forest_path, 1, &[0x52u8; 32][..]));
+
+        assert!(!verify(&[], 0, &message[..]));
+        let mut short_sibling = forest_path.clone();
+        short_sibling[0].pop();
+        assert!(!verify(&short_sibling, 1, &message[..]));
+    }
+
+    #[test]
+    fn single_leaf_tree_root_is_the_leaf() {
+        let poseidon = PoseidonContext::new();
+        let parameter = [KoalaBear::from_u32(5); PARAMETER_LEN_FE];
+        let leaf = [KoalaBear::from_u32(11); HASH_LEN_FE];
+        assert_eq!(
+            merkle_root_from_path(&poseidon, &parameter, 0, &leaf, &[]),
+            leaf
+        );
+        // Full verification only accepts TREE_HEIGHT paths, so height 0 never reaches the leaf
+        // comparison.
+        assert!(hash_tree_root(&poseidon, &parameter, 0, &[leaf], &[]).is_none());
+        let mut batch = dummy_batch(1);
+        batch.params.tree_height = 0;
+        batch.witness.signatures[0].auth_path.clear();
+        assert_eq!(verify_batch(&batch), (false, 0));
+    }
+
+    #[test]
+    fn computed_root_matches_public_root() {
+        let message = [0x33u8; 32];
+        let epoch = 0;
+        let sig = synthetic_signature(9, epoch);
+        let pk = synthetic_key(&sig, 9, &message, epoch);
+
+        let root = compute_root(&w1_params(), &sig, &message, epoch as u64, &pk).unwrap();
+        assert_eq!(field_array_to_bytes(&root), pk.root);
+
+        let other = compute_root(&w1_params(), &sig, &[0x34u8; 32], epoch as u64, &pk).unwrap();
+        assert_ne!(field_array_to_bytes(&other), pk.root);
+        assert!(compute_root(&w1_params(), &sig, &message[..31], epoch as u64, &pk).is_none());
+    }
+
+    #[test]
+    fn accepts_signature_matching_second_acceptable_root() {
+        let message = [0x55u8; 32];
+        let epoch = 0;
+        let poseidon = PoseidonContext::new();
+        let sig = synthetic_signature(6, epoch);
+        let real = synthetic_key(&sig, 6, &message, epoch);
+        let stale_root = field_bytes(77, HASH_LEN_FE);
+
+        let mut rotated = real.clone();
+        rotated.root = field_bytes(88, HASH_LEN_FE);
+        assert_eq!(
+            verify_one(&sig, &rotated, &message, epoch, &poseidon),
+            Err(VerifyError::RootMismatch)
+        );
+
+        rotated.acceptable_roots = vec![stale_root.clone(), real.root.clone()];
+        assert!(verify_one(&sig, &rotated, &message, epoch, &poseidon).is_ok());
+
+        rotated.acceptable_roots = vec![stale_root];
+        assert_eq!(
+            verify_one(&sig, &rotated, &message, epoch, &poseidon),
+            Err(VerifyError::RootMismatch)
+        );
+    }
+
+    #[test]
+    fn commitment_binds_acceptable_roots() {
+        let mut stmt = dummy_batch(1).statement;
+        let base = statement_commitment(&stmt);
+        stmt.public_keys[0].acceptable_roots = vec![field_bytes(1, HASH_LEN_FE)];
+        assert_ne!(statement_commitment(&stmt), base);
+    }
+
+    #[test]
+    fn leaf_index_follows_epoch_within_lifetime() {
+        assert_eq!(leaf_index_for_epoch(0, 18), Some(0));
+        assert_eq!(leaf_index_for_epoch(12_345, 18), Some(12_345));
+        assert_eq!(leaf_index_for_epoch((1 << 18) - 1, 18), Some((1 << 18) - 1));
+        assert_eq!(leaf_index_for_epoch(1 << 18, 18), None);
+        assert_eq!(leaf_index_for_epoch(u64::MAX, 64), None);
+    }
+
+    #[test]
+    fn rejects_leaf_index_that_does_not_match_epoch() {
+        let message = [0x66u8; 32];
+        let epoch = 0;
+        let sig = synthetic_signature(3, epoch);
+        let pk = synthetic_key(&sig, 3, &message, epoch);
+        let poseidon = PoseidonContext::new();
+        assert!(verify_one(&sig, &pk, &message, epoch, &poseidon).is_ok());
+
+        let mut shifted = sig.clone();
+        shifted.leaf_index = 1;
+        assert_eq!(
+            verify_one(&shifted, &pk, &message, epoch, &poseidon),
+            Err(VerifyError::LeafIndexMismatch)
+        );
+    }
+
+    #[test]
+    fn counts_distinct_signers_by_root() {
+        let mut stmt = dummy_batch(3).statement;
+        stmt.public_keys[0].root = field_bytes(1, HASH_LEN_FE);
+        stmt.public_keys[1].root = field_bytes(2, HASH_LEN_FE);
+        stmt.public_keys[2].root = field_bytes(1, HASH_LEN_FE);
+        assert_eq!(distinct_signers(&stmt), 2);
+        assert_eq!(distinct_signers(&dummy_batch(0).statement), 0);
+    }
+
+    #[test]
+    fn injected_failures_are_rejected_with_matching_errors() {
+        // The host's `InjectedFailure` defects, applied to a batch whose first entry verifies.
+        // Batch-level defects surface from `check_batch`; the others pass it and are caught when
+        // `verify_one` checks the signature.
+        let mut valid = dummy_batch(2);
+        let sig = synthetic_signature(3, 0);
+        valid.statement.public_keys[0] = synthetic_key(&sig, 3, &valid.statement.m, 0);
+        valid.witness.signatures[0] = sig;
+        let verify_first = |batch: &VerificationBatch| {
+            verify_one(
+                &batch.witness.signatures[0],
+                &batch.statement.public_keys[0],
+                &batch.statement.m,
+                0,
+                &PoseidonContext::new(),
+            )
+        };
+        assert!(check_batch(&valid).is_ok());
+        assert_eq!(verify_first(&valid), Ok(()));
+
+        type Defect = fn(&mut VerificationBatch);
+        let batch_level: [(Defect, VerifyError); 2] = [
+            (
+                |b| {
+                    b.witness.signatures[0].auth_path.pop();
+                },
+                VerifyError::TreeHeightMismatch,
+            ),
+            (|b| b.params.v += 1, VerifyError::UnsupportedParams),
+        ];
+        for (apply, expected) in batch_level {
+            let mut batch = valid.clone();
+            apply(&mut batch);
+            assert_eq!(check_batch(&batch).err(), Some(expected));
+        }
+
+        let per_signature: [(Defect, VerifyError); 3] = [
+            (
+                |b| {
+                    b.witness.signatures[0].wots_chain_ends.pop();
+                },
+                VerifyError::MalformedEntry,
+            ),
+            (
+                |b| b.statement.public_keys[0].root[0] ^= 1,
+                VerifyError::RootMismatch,
+            ),
+            (
+                |b| b.witness.signatures[0].leaf_index ^= 1,
+                VerifyError::LeafIndexMismatch,
+            ),
+        ];
+        for (apply, expected) in per_signature {
+            let mut batch = valid.clone();
+            apply(&mut batch);
+            assert!(check_batch(&batch).is_ok(), "{expected:?}");
+            assert_eq!(verify_first(&batch), Err(expected));
+        }
+    }
+
+    #[test]
+    fn revealed_witness_bytes_match_total_bytes() {
+        let batch = dummy_batch(3);
+        let expected = batch.witness.total_bytes();
+        assert!(expected > 0);
+        assert_eq!(batch.witness.revealed_bytes() as usize, expected);
+        assert_eq!(dummy_batch(0).witness.revealed_bytes(), 0);
+
+        let mut truncated = batch.witness.clone();
+        truncated.signatures[2].auth_path.pop();
+        assert!(truncated.revealed_bytes() < batch.witness.revealed_bytes());
+    }
+
+    // Needs full WOTS verification, which `insecure-merkle-only` builds skip.
+    #[cfg(not(feature = "insecure-merkle-only"))]
+    #[test]
+    fn verifies_only_the_requested_range() {
+        let message = [0x77u8; 32];
+        let epoch = 0;
+        let signatures: Vec<_> = (1..=5)
+            .map(|seed| synthetic_signature(seed, epoch))
+            .collect();
+        let mut public_keys: Vec<_> = signatures
+            .iter()
+            .zip(1..=5)
+            .map(|(sig, seed)| synthetic_key(sig, seed, &message, epoch))
+            .collect();
+        // Keys outside the range are never checked against a signature.
+        public_keys[0].root = field_bytes(1, HASH_LEN_FE);
+        let mut batch = VerificationBatch {
+            params: w1_params(),
+            statement: Statement {
+                k: 5,
+                ep: epoch as u64,
+                m: message.to_vec(),
+                public_keys,
+                range: Some((2, 4)),
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+            },
+            witness: Witness {
+                signatures: signatures[2..4].to_vec(),
+            },
+        };
+        assert_eq!(signature_range(&batch.statement), Some((2, 4)));
+        assert_eq!(verify_batch(&batch), (true, 2));
+
+        let full = statement_commitment(&batch.statement);
+        batch.statement.range = Some((1, 3));
+        assert_ne!(statement_commitment(&batch.statement), full);
+        assert_eq!(verify_batch(&batch), (false, 2));
+
+        batch.statement.range = Some((4, 6));
+        assert_eq!(verify_batch(&batch), (false, 0));
+        batch.statement.range = Some((3, 2));
+        assert_eq!(verify_batch(&batch), (false, 0));
+    }
+
+    // Needs full WOTS verification, which `insecure-merkle-only` builds skip.
+    #[cfg(not(feature = "insecure-merkle-only"))]
+    #[test]
+    fn lenient_mode_ignores_trailing_public_keys() {
+        let message = [0x55u8; 32];
+        let signatures: Vec<_> = (1..=3).map(|seed| synthetic_signature(seed, 0)).collect();
+        let mut public_keys: Vec<_> = signatures
+            .iter()
+            .zip(1..=3)
+            .map(|(sig, seed)| synthetic_key(sig, seed, &message, 0))
+            .collect();
+        let strict = Statement {
+            k: 3,
+            ep: 0,
+            m: message.to_vec(),
+            public_keys: public_keys.clone(),
+            range: None,
+            allow_extra_public_keys: false,
+            epoch_window: None,
+            randomness_commitment: None,
+            hybrid_ed25519: false,
+        };
+        // Two trailing keys that match no signature and are not even well formed.
+        public_keys.push(PublicKey {
+            root: vec![1; 3],
+            parameter: Vec::new(),
+            acceptable_roots: Vec::new(),
+            ed25519_key: None,
+        });
+        public_keys.push(public_keys[0].clone());
+        let mut batch = VerificationBatch {
+            params: w1_params(),
+            statement: Statement {
+                public_keys,
+                allow_extra_public_keys: true,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+                ..strict.clone()
+            },
+            witness: Witness { signatures },
+        };
+
+        assert_eq!(verify_batch(&batch), (true, 3));
+        assert_eq!(
+            statement_commitment(&batch.statement),
+            statement_commitment(&strict)
+        );
+        assert_eq!(distinct_signers(&batch.statement), 3);
+
+        batch.statement.allow_extra_public_keys = false;
+        assert_eq!(verify_batch(&batch), (false, 0));
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn profiled_verification_counts_hash_calls() {
+        let message = [0x77u8; 32];
+        let sig = synthetic_signature(1, 0);
+        let pk = synthetic_key(&sig, 1, &message, 0);
+        let mut batch = VerificationBatch {
+            params: w1_params(),
+            statement: Statement {
+                k: 1,
+                ep: 0,
+                m: message.to_vec(),
+                public_keys: vec![pk],
+                range: None,
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+            },
+            witness: Witness {
+                signatures: vec![sig],
+            },
+        };
+        let (result, hash_calls) = verify_batch_profiled(&batch);
+        assert_eq!(result, verify_batch(&batch));
+        assert!(hash_calls > 0);
+
+        batch.statement.k = 2;
+        assert_eq!(verify_batch_profiled(&batch), ((false, 0), 0));
+    }
+
+    /// Pinned in the host's `verify_commitment` tests too: both sides must agree on the
+    /// commitment bytes and on the little-endian word order used to reveal them.
+    const FIXED_COMMITMENT_WORDS: [u32; 8] = [
+        0xf61b_2fe1,
+        0xbd34_0905,
+        0x126f_8a86,
+        0x7a6c_4d44,
+        0x28f5_604e,
+        0x116e_864d,
+        0x1c0f_dfe0,
+        0x663a_8415,
+    ];
+
+    #[test]
+    fn commitment_words_reassemble_into_digest() {
+        let stmt = Statement {
+            k: 1,
+            ep: 5,
+            m: vec![0x11; 32],
+            public_keys: vec![PublicKey {
+                root: vec![0x22; 28],
+                parameter: vec![0x33; 20],
+                acceptable_roots: Vec::new(),
+                ed25519_key: None,
+            }],
+            range: None,
+            allow_extra_public_keys: false,
+            epoch_window: None,
+            randomness_commitment: None,
+            hybrid_ed25519: false,
+        };
+        let digest = statement_commitment(&stmt);
+        let words = digest_words_le(&digest);
+        assert_eq!(words, FIXED_COMMITMENT_WORDS);
+
+        let reassembled: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        assert_eq!(reassembled, digest);
+        assert_eq!(digest[..4], [0xe1, 0x2f, 0x1b, 0xf6]);
+    }
+
+    #[test]
+    fn rejects_batch_with_inconsistent_auth_path_height() {
+        let mut batch = dummy_batch(2);
+        batch.witness.signatures[1].auth_path.pop();
+        assert_eq!(verify_batch(&batch), (false, 0));
+    }
+
+    #[cfg(feature = "insecure-merkle-only")]
+    #[test]
+    fn merkle_only_checks_just_the_authentication_path() {
+        let epoch = 0;
+        let poseidon = PoseidonContext::new();
+        let sig = synthetic_signature(8, epoch);
+        let parameter_bytes = synthetic_parameter(8);
+        let parameter = bytes_to_field_array::<PARAMETER_LEN_FE>(&parameter_bytes).unwrap();
+        let leaf = decode_domains(&sig.wots_chain_ends).unwrap();
+        let path = decode_domains(&sig.auth_path).unwrap();
+        let root = hash_tree_root(&poseidon, &parameter, epoch, &leaf, &path).unwrap();
+        let pk = PublicKey {
+            root: field_array_to_bytes(&root),
+            parameter: parameter_bytes,
+            acceptable_roots: Vec::new(),
+            ed25519_key: None,
+        };
+
+        assert!(verify_merkle_only(&sig, &pk, epoch, &poseidon).is_ok());
+        // The full verifier walks the chains first, so it does not reach the same root.
+        assert_eq!(
+            verify_one(&sig, &pk, &[0u8; 32], epoch, &poseidon),
+            Err(VerifyError::RootMismatch)
+        );
+
+        let mut tampered = sig.clone();
+        tampered.auth_path[3] = field_bytes(1, HASH_LEN_FE);
+        assert_eq!(
+            verify_merkle_only(&tampered, &pk, epoch, &poseidon),
+            Err(VerifyError::RootMismatch)
+        );
+    }
+
+    #[test]
+    fn malformed_batches_report_their_error() {
+        let check = |edit: fn(&mut VerificationBatch)| {
+            let mut batch = dummy_batch(2);
+            edit(&mut batch);
+            check_batch(&batch).err()
+        };
+        assert_eq!(check(|_| {}), None);
+        assert_eq!(
+            check(|b| b.statement.range = Some((1, 3))),
+            Some(VerifyError::InvalidRange)
+        );
+        assert_eq!(
+            check(|b| b.statement.public_keys.truncate(1)),
+            Some(VerifyError::PublicKeyCountMismatch)
+        );
+        assert_eq!(
+            check(|b| b.witness.signatures.truncate(1)),
+            Some(VerifyError::SignatureCountMismatch)
+        );
+        assert_eq!(
+            check(|b| b.params.w = 4),
+            Some(VerifyError::UnsupportedParams)
+        );
+        assert_eq!(
+            check(|b| {
+                b.witness.signatures[1].auth_path.pop();
+            }),
+            Some(VerifyError::TreeHeightMismatch)
+        );
+        assert_eq!(
+            check(|b| b.statement.ep = 1 << 32),
+            Some(VerifyError::EpochOutOfRange)
+        );
+    }
+
+    #[test]
+    fn malformed_signatures_report_their_error() {
+        let message = [0x31u8; 32];
+        let epoch = 0;
+        let sig = synthetic_signature(4, epoch);
+        let pk = synthetic_key(&sig, 4, &message, epoch);
+        let poseidon = PoseidonContext::new();
+        let verify = |sig: &Signature, pk: &PublicKey, message: &[u8]| {
+            verify_one(sig, pk, message, epoch, &poseidon)
+        };
+
+        let mut short_randomness = sig.clone();
+        short_randomness.randomness.pop();
+        assert_eq!(
+            verify(&short_randomness, &pk, &message[..]),
+            Err(VerifyError::MalformedEntry)
+        );
+
+        let mut missing_chain = sig.clone();
+        missing_chain.wots_chain_ends.pop();
+        assert_eq!(
+            verify(&missing_chain, &pk, &message[..]),
+            Err(VerifyError::MalformedEntry)
+        );
+
+        let mut short_root = pk.clone();
+        short_root.root.pop();
+        assert_eq!(
+            verify(&sig, &short_root, &message[..]),
+            Err(VerifyError::MalformedEntry)
+        );
+
+        assert_eq!(
+            verify(&sig, &pk, &message[..31]),
+            Err(VerifyError::MalformedMessage)
+        );
+        assert_eq!(
+            verify(&sig, &pk, &[0x32u8; 32][..]),
+            Err(VerifyError::RootMismatch)
+        );
+    }
+
+    #[test]
+    fn overlong_randomness_is_malformed() {
+        let message = [0x41u8; 32];
+        let epoch = 0;
+        let sig = synthetic_signature(6, epoch);
+        let pk = synthetic_key(&sig, 6, &message, epoch);
+        let poseidon = PoseidonContext::new();
+        assert_eq!(verify_one(&sig, &pk, &message, epoch, &poseidon), Ok(()));
+
+        // Extra trailing bytes are rejected up front rather than truncated away.
+        let mut overlong = sig.clone();
+        overlong.randomness.extend_from_slice(&field_bytes(1, 1));
+        assert_eq!(
+            verify_one(&overlong, &pk, &message, epoch, &poseidon),
+            Err(VerifyError::MalformedEntry)
+        );
+    }
+
+    #[test]
+    fn rejects_epochs_outside_the_window() {
+        let mut batch = dummy_batch(2);
+        batch.statement.ep = 5;
+        let unbounded = statement_commitment(&batch.statement);
+
+        batch.statement.epoch_window = Some((5, 5));
+        assert_eq!(check_batch(&batch).map(|(.., epoch)| epoch), Ok(5));
+        batch.statement.epoch_window = Some((0, 10));
+        assert!(check_batch(&batch).is_ok());
+        assert_ne!(statement_commitment(&batch.statement), unbounded);
+
+        for window in [(6, 10), (0, 4), (10, 0)] {
+            batch.statement.epoch_window = Some(window);
+            assert_eq!(
+                check_batch(&batch).err(),
+                Some(VerifyError::EpochOutsideWindow)
+            );
+            assert_eq!(verify_batch(&batch), (false, 0));
+        }
+    }
+
+    #[test]
+    fn rejects_randomness_changed_after_commitment() {
+        let mut batch = dummy_batch(2);
+        batch.witness.signatures[1].randomness = field_bytes(3, RANDOMNESS_LEN_FE);
+        let uncommitted = statement_commitment(&batch.statement);
+        batch.statement.randomness_commitment =
+            Some(randomness_commitment(&batch.witness.signatures));
+        assert!(check_batch(&batch).is_ok());
+        assert_ne!(statement_commitment(&batch.statement), uncommitted);
+
+        batch.witness.signatures[1].randomness[0] ^= 1;
+        assert_eq!(
+            check_batch(&batch).err(),
+            Some(VerifyError::RandomnessCommitmentMismatch)
+        );
+        assert_eq!(verify_batch(&batch), (false, 0));
+    }
+
+    #[test]
+    fn consistent_heights_reach_per_signature_checks() {
+        let batch = dummy_batch(2);
+        assert_eq!(verify_batch(&batch), (false, 2));
+    }
+}
@@ -28,6 +28,11 @@ fn main() {
         ep: 0,
         m: hash_message_to_digest(&[]).to_vec(),
         public_keys: vec![],
+        range: None,
+        allow_extra_public_keys: false,
+        epoch_window: None,
+        randomness_commitment: None,
+        hybrid_ed25519: false,
     };
     let witness = Witness { signatures: vec![] };
     let batch = VerificationBatch {
@@ -0,0 +1,69 @@
+use std::error::Error;
+use std::fs;
+use std::time::{Duration, Instant};
+
+use crate::commands::CommandResult;
+use crate::utils::{input::generate_batch_input, mem::fmt_bytes};
+
+/// Timing of repeated input generation runs.
+#[derive(Debug, Clone, Copy)]
+pub struct GenBenchmark {
+    pub average: Duration,
+    pub file_size: u64,
+}
+
+/// Time `generate_batch_input` alone over `iterations` runs. Inputs go to a scratch file in
+/// the temp directory so the checked-in `guest/input.json` is left untouched.
+pub fn benchmark_input_generation(
+    signatures: usize,
+    iterations: u32,
+) -> Result<GenBenchmark, Box<dyn Error>> {
+    if iterations == 0 {
+        return Err("iterations must be at least 1".into());
+    }
+    let out = std::env::temp_dir().join(format!("xmss-bench-gen-{}.json", std::process::id()));
+    let out_str = out.to_str().ok_or("temp path is not valid UTF-8")?;
+
+    let mut total = Duration::ZERO;
+    for _ in 0..iterations {
+        let t0 = Instant::now();
+        generate_batch_input(signatures, out_str)?;
+        total += t0.elapsed();
+    }
+    let file_size = fs::metadata(&out)?.len();
+    let _ = fs::remove_file(&out);
+
+    Ok(GenBenchmark {
+        average: total / iterations,
+        file_size,
+    })
+}
+
+/// `benchmark-gen`: report the average input generation time and input size.
+pub fn run(signatures: usize, iterations: u32) -> CommandResult {
+    println!(
+        "=== Input Generation Benchmark ({} signatures, {} iterations) ===\n",
+        signatures, iterations
+    );
+    let result = benchmark_input_generation(signatures, iterations)?;
+    println!("Average generation time: {:?}", result.average);
+    println!("Input file size:         {}", fmt_bytes(result.file_size));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_positive_duration_for_two_signatures() {
+        let result = benchmark_input_generation(2, 1).unwrap();
+        assert!(result.average > Duration::ZERO);
+        assert!(result.file_size > 0);
+    }
+
+    #[test]
+    fn zero_iterations_is_an_error() {
+        assert!(benchmark_input_generation(2, 0).is_err());
+    }
+}
@@ -1,15 +1,115 @@
 use crate::commands::CommandResult;
 use crate::utils::{
-    input::generate_batch_input,
+    backend::{OpenVmBackend, ProofBackend},
+    decode::batch_from_input_json,
+    input::{generate_batch_input_with, warn_on_identical_signatures, BatchInputOptions},
     mem::{children_maxrss_bytes, fmt_bytes},
-    openvm::run_in_guest,
     to_abs,
 };
-use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use xmss_types::VerificationBatch;
+
+/// Prove and verify timings, proof size, and peak memory for one input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunMetrics {
+    pub prove: Duration,
+    pub verify: Duration,
+    pub proof_size_bytes: u64,
+    /// Peak resident memory of the prover processes so far, or `None` where the platform
+    /// cannot measure it (as opposed to a measured 0).
+    pub peak_memory_bytes: Option<u64>,
+}
+
+/// One completed run in a results file, which holds one JSON object per line.
+#[derive(Debug, Serialize, Deserialize)]
+struct RunRecord {
+    /// File name of the input within the benchmarked directory.
+    input: String,
+    prove_us: u64,
+    verify_us: u64,
+    proof_size_bytes: u64,
+    /// `null` when memory was not measured; absent in files written before it was recorded.
+    peak_memory_bytes: Option<u64>,
+}
+
+impl RunRecord {
+    fn new(input: String, metrics: &RunMetrics) -> Self {
+        RunRecord {
+            input,
+            prove_us: metrics.prove.as_micros() as u64,
+            verify_us: metrics.verify.as_micros() as u64,
+            proof_size_bytes: metrics.proof_size_bytes,
+            peak_memory_bytes: metrics.peak_memory_bytes,
+        }
+    }
+
+    fn metrics(&self) -> RunMetrics {
+        RunMetrics {
+            prove: Duration::from_micros(self.prove_us),
+            verify: Duration::from_micros(self.verify_us),
+            proof_size_bytes: self.proof_size_bytes,
+            peak_memory_bytes: self.peak_memory_bytes,
+        }
+    }
+}
+
+/// Totals over several [`RunMetrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunSummary {
+    pub runs: usize,
+    pub total_prove: Duration,
+    pub total_verify: Duration,
+    pub average_proof_size_bytes: u64,
+}
+
+impl RunSummary {
+    pub fn from_runs<'a>(runs: impl IntoIterator<Item = &'a RunMetrics>) -> Self {
+        let mut summary = RunSummary {
+            runs: 0,
+            total_prove: Duration::ZERO,
+            total_verify: Duration::ZERO,
+            average_proof_size_bytes: 0,
+        };
+        let mut total_size = 0u64;
+        for run in runs {
+            summary.runs += 1;
+            summary.total_prove += run.prove;
+            summary.total_verify += run.verify;
+            total_size += run.proof_size_bytes;
+        }
+        if summary.runs > 0 {
+            summary.average_proof_size_bytes = total_size / summary.runs as u64;
+        }
+        summary
+    }
+}
+
+/// Bytes the batch's signatures take when shipped individually, to compare with the one proof
+/// that replaces them. A signature counts its leaf index, randomness, and every chain end and
+/// path node it carries, without encoding overhead.
+pub fn individual_signature_bytes(batch: &VerificationBatch) -> usize {
+    batch
+        .witness
+        .signatures
+        .iter()
+        .map(|sig| {
+            let nodes = sig.wots_chain_ends.iter().chain(&sig.auth_path);
+            size_of::<u32>() + sig.randomness.len() + nodes.map(Vec::len).sum::<usize>()
+        })
+        .sum()
+}
 
 /// Run the default XMSS workflow: generate input, prove, and verify in sequence.
 /// Parameters such as signature count or iteration count are fixed to keep the CLI simple.
-pub fn run_default_workflow() -> CommandResult {
+/// The input is generated with `options`; with `inject_failure` set, it carries that defect and
+/// the proof should attest `all_valid = 0`.
+pub fn run_default_workflow(options: &BatchInputOptions) -> CommandResult {
     const SIGNATURES: usize = 2;
     let input = "guest/input.json";
 
@@ -17,18 +117,66 @@ pub fn run_default_workflow() -> CommandResult {
 
     // Generate input
     println!("Generating input with {} signatures...", SIGNATURES);
+    if options.activation_epoch != 0 || options.signing_epoch != 0 {
+        println!(
+            "Activation epoch: {}, signing epoch: {}",
+            options.activation_epoch, options.signing_epoch
+        );
+    }
+    if let Some(failure) = options.inject_failure {
+        println!("Injecting failure: {}", failure.name());
+    }
     let t0 = Instant::now();
-    generate_batch_input(SIGNATURES, input)?;
+    generate_batch_input_with(SIGNATURES, input, options)?;
     let input_gen_time = t0.elapsed();
     println!("Input generation time: {:?}\n", input_gen_time);
 
+    let RunMetrics {
+        prove: prove_time,
+        verify: verify_time,
+        proof_size_bytes,
+        ..
+    } = prove_and_verify(&OpenVmBackend::default(), &to_abs(input)?)?;
+
+    // Summary
+    let total_time = input_gen_time + prove_time + verify_time;
+    println!("=== Summary ===");
+    println!("Input generation: {:?}", input_gen_time);
+    println!("Prove:            {:?}", prove_time);
+    println!("Verify:           {:?}", verify_time);
+    println!("Total:            {:?}", total_time);
+    println!("Proof size:       {}", fmt_bytes(proof_size_bytes));
+    let batch = batch_from_input_json(&fs::read_to_string(input)?)?;
+    let individual = individual_signature_bytes(&batch);
+    println!("Signatures alone: {}", fmt_bytes(individual as u64));
+    if proof_size_bytes > 0 {
+        println!(
+            "Compression:      {:.2}x",
+            individual as f64 / proof_size_bytes as f64
+        );
+    }
+
+    if let Some(bytes) = children_maxrss_bytes() {
+        println!("Final peak memory: {}", fmt_bytes(bytes));
+    }
+
+    Ok(())
+}
+
+/// Prove then verify one absolute input path with `backend`, printing each phase's time and
+/// peak memory, and measure the size of the written proof.
+fn prove_and_verify(
+    backend: &impl ProofBackend,
+    input_abs: &Path,
+) -> Result<RunMetrics, Box<dyn Error>> {
     // Prove
     println!("Running prove...");
-    let input_abs = to_abs(input)?;
     let t0 = Instant::now();
-    run_in_guest(["prove", "app", "--input", input_abs.to_str().unwrap()])?;
-    let prove_time = t0.elapsed();
-    println!("Prove time: {:?}", prove_time);
+    let proof_path = backend.prove(input_abs)?;
+    let prove = t0.elapsed();
+    println!("Prove time: {:?}", prove);
+    let proof_size_bytes = fs::metadata(&proof_path)?.len();
+    println!("Proof size: {}", fmt_bytes(proof_size_bytes));
     if let Some(bytes) = children_maxrss_bytes() {
         println!("Peak memory (prove): {}\n", fmt_bytes(bytes));
     }
@@ -36,24 +184,353 @@ pub fn run_default_workflow() -> CommandResult {
     // Verify
     println!("Running verify...");
     let t0 = Instant::now();
-    run_in_guest(["verify", "app"])?;
-    let verify_time = t0.elapsed();
-    println!("Verify time: {:?}", verify_time);
-    if let Some(bytes) = children_maxrss_bytes() {
+    backend.verify(&proof_path)?;
+    let verify = t0.elapsed();
+    println!("Verify time: {:?}", verify);
+    let peak_memory_bytes = children_maxrss_bytes();
+    if let Some(bytes) = peak_memory_bytes {
         println!("Peak memory (verify): {}\n", fmt_bytes(bytes));
     }
 
-    // Summary
-    let total_time = input_gen_time + prove_time + verify_time;
-    println!("=== Summary ===");
-    println!("Input generation: {:?}", input_gen_time);
-    println!("Prove:            {:?}", prove_time);
-    println!("Verify:           {:?}", verify_time);
-    println!("Total:            {:?}", total_time);
+    Ok(RunMetrics {
+        prove,
+        verify,
+        proof_size_bytes,
+        peak_memory_bytes,
+    })
+}
 
-    if let Some(bytes) = children_maxrss_bytes() {
-        println!("Final peak memory: {}", fmt_bytes(bytes));
+/// Run `run` on every `*.json` file in `dir`, in file-name order. Other entries are skipped
+/// with a warning.
+///
+/// With a `results_file`, each completed run is appended to it as soon as it finishes, so an
+/// interrupted benchmark keeps what it measured. With `resume`, runs already in that file are
+/// reused instead of repeated; without it, the file is started afresh.
+pub fn benchmark_input_dir_with<F>(
+    dir: &Path,
+    results_file: Option<&Path>,
+    resume: bool,
+    mut run: F,
+) -> Result<Vec<(PathBuf, RunMetrics)>, Box<dyn Error>>
+where
+    F: FnMut(&Path) -> Result<RunMetrics, Box<dyn Error>>,
+{
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    paths.sort();
+
+    let mut recorded = HashMap::new();
+    let mut log = match results_file {
+        Some(path) => {
+            if resume && path.exists() {
+                for line in fs::read_to_string(path)?.lines() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let record: RunRecord = serde_json::from_str(line)?;
+                    recorded.insert(record.input.clone(), record.metrics());
+                }
+            }
+            let mut options = OpenOptions::new();
+            if resume {
+                options.create(true).append(true);
+            } else {
+                options.create(true).write(true).truncate(true);
+            }
+            Some(options.open(path)?)
+        }
+        None => None,
+    };
+
+    let mut results = Vec::new();
+    for path in paths {
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "json") {
+            eprintln!("Skipping {} (not a .json input)", path.display());
+            continue;
+        }
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        if let Some(metrics) = recorded.remove(&name) {
+            println!("=== {} (already recorded) ===", path.display());
+            results.push((path, metrics));
+            continue;
+        }
+        println!("=== {} ===", path.display());
+        let metrics = run(&fs::canonicalize(&path)?)?;
+        if let Some(log) = log.as_mut() {
+            let line = serde_json::to_string(&RunRecord::new(name, &metrics))? + "\n";
+            log.write_all(line.as_bytes())?;
+            log.flush()?;
+        }
+        results.push((path, metrics));
     }
+    Ok(results)
+}
 
+/// `benchmark-openvm --input-dir`: prove and verify every input in `dir` and summarize,
+/// optionally recording runs to (and resuming from) a `results_file`.
+pub fn run_input_dir(dir: &Path, results_file: Option<&Path>, resume: bool) -> CommandResult {
+    let results = benchmark_input_dir_with(dir, results_file, resume, |input| {
+        // Inputs that fail to decode are left for the prover to reject.
+        if let Ok(batch) = batch_from_input_json(&fs::read_to_string(input)?) {
+            warn_on_identical_signatures(&batch, input);
+        }
+        prove_and_verify(&OpenVmBackend::default(), input)
+    })?;
+    if results.is_empty() {
+        return Err(format!("no .json inputs found in {}", dir.display()).into());
+    }
+
+    println!("=== Summary ({} inputs) ===", results.len());
+    for (path, metrics) in &results {
+        let memory = metrics
+            .peak_memory_bytes
+            .map_or_else(|| "not measured".to_string(), fmt_bytes);
+        println!(
+            "{}: prove {:?}, verify {:?}, proof {}, peak memory {}",
+            path.display(),
+            metrics.prove,
+            metrics.verify,
+            fmt_bytes(metrics.proof_size_bytes),
+            memory
+        );
+    }
+    let summary = RunSummary::from_runs(results.iter().map(|(_, metrics)| metrics));
+    println!("Total prove:        {:?}", summary.total_prove);
+    println!("Total verify:       {:?}", summary.total_verify);
+    println!(
+        "Average proof size: {}",
+        fmt_bytes(summary.average_proof_size_bytes)
+    );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::input::generate_batch_input;
+    use std::cell::RefCell;
+    use xmss_types::{Signature, Statement, TslParams, Witness};
+
+    /// Writes a fixed-size proof file and records each call, without running a prover.
+    #[derive(Default)]
+    struct MockBackend {
+        calls: RefCell<Vec<String>>,
+    }
+
+    impl ProofBackend for MockBackend {
+        fn prove(&self, input: &Path) -> Result<PathBuf, Box<dyn Error>> {
+            self.calls.borrow_mut().push("prove".into());
+            let proof = input.with_extension("mock.proof");
+            fs::write(&proof, [0u8; 64])?;
+            Ok(proof)
+        }
+
+        fn verify(&self, proof: &Path) -> Result<(), Box<dyn Error>> {
+            self.calls.borrow_mut().push("verify".into());
+            if fs::metadata(proof)?.len() == 64 {
+                Ok(())
+            } else {
+                Err("proof rejected".into())
+            }
+        }
+    }
+
+    #[test]
+    fn prove_and_verify_runs_against_any_backend() {
+        let dir = std::env::temp_dir().join(format!("xmss-bench-mock-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.json");
+        fs::write(&input, "{}").unwrap();
+
+        let backend = MockBackend::default();
+        let metrics = prove_and_verify(&backend, &input).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(metrics.proof_size_bytes, 64);
+        assert_eq!(*backend.calls.borrow(), ["prove", "verify"]);
+    }
+
+    #[test]
+    fn input_dir_runs_every_json_file() {
+        let dir = std::env::temp_dir().join(format!("xmss-bench-dir-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["a.json", "b.json", "notes.txt"] {
+            fs::write(dir.join(name), "{}").unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let results = benchmark_input_dir_with(&dir, None, false, |path| {
+            seen.push(path.file_name().unwrap().to_owned());
+            Ok(RunMetrics {
+                prove: Duration::from_millis(2),
+                verify: Duration::from_millis(1),
+                proof_size_bytes: 1024,
+                peak_memory_bytes: None,
+            })
+        })
+        .unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(seen, ["a.json", "b.json"]);
+    }
+
+    #[test]
+    fn resume_continues_an_interrupted_run() {
+        let dir = std::env::temp_dir().join(format!("xmss-bench-resume-{}", std::process::id()));
+        let inputs = dir.join("inputs");
+        fs::create_dir_all(&inputs).unwrap();
+        for name in ["a.json", "b.json", "c.json"] {
+            fs::write(inputs.join(name), "{}").unwrap();
+        }
+        let log = dir.join("results.jsonl");
+        let metrics = |size| RunMetrics {
+            prove: Duration::from_micros(5),
+            verify: Duration::from_micros(3),
+            proof_size_bytes: size,
+            peak_memory_bytes: Some(size * 10),
+        };
+
+        // The first run stops at b.json, after a.json was recorded.
+        let interrupted = benchmark_input_dir_with(&inputs, Some(&log), false, |path| {
+            if path.ends_with("b.json") {
+                Err("prover crashed".into())
+            } else {
+                Ok(metrics(100))
+            }
+        });
+        assert!(interrupted.is_err());
+        assert_eq!(fs::read_to_string(&log).unwrap().lines().count(), 1);
+
+        let mut rerun = Vec::new();
+        let results = benchmark_input_dir_with(&inputs, Some(&log), true, |path| {
+            rerun.push(path.file_name().unwrap().to_owned());
+            Ok(metrics(200))
+        })
+        .unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(rerun, ["b.json", "c.json"]);
+        let sizes: Vec<_> = results.iter().map(|(_, m)| m.proof_size_bytes).collect();
+        assert_eq!(sizes, [100, 200, 200]);
+        assert_eq!(results[0].1, metrics(100));
+    }
+
+    /// Generate, prove, and verify a real input with cargo-openvm. Run it explicitly with
+    /// `cargo test -p xmss-host -- --ignored`; it fails if cargo-openvm is missing.
+    #[test]
+    #[ignore = "needs the OpenVM toolchain and takes minutes"]
+    fn openvm_pipeline_round_trip() {
+        let has_openvm = std::process::Command::new("cargo")
+            .args(["openvm", "--version"])
+            .output()
+            .is_ok_and(|out| out.status.success());
+        assert!(
+            has_openvm,
+            "cargo-openvm not found; install it to run this test"
+        );
+        // Cargo runs tests in host/, so point the backend at the guest crate explicitly.
+        let backend = OpenVmBackend {
+            guest_dir: concat!(env!("CARGO_MANIFEST_DIR"), "/../guest").into(),
+        };
+        let dir = std::env::temp_dir().join(format!("xmss-pipeline-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.json");
+        generate_batch_input(2, input.to_str().unwrap()).unwrap();
+
+        let metrics = prove_and_verify(&backend, &input);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(metrics.unwrap().proof_size_bytes > 0);
+    }
+
+    #[test]
+    fn savings_count_every_signature_byte() {
+        let signature = Signature {
+            leaf_index: 0,
+            randomness: vec![1; 20],
+            wots_chain_ends: vec![vec![2; 28]; 3],
+            auth_path: vec![vec![3; 28]; 2],
+            auth_path_mask: None,
+            ed25519_signature: None,
+        };
+        let mut batch = VerificationBatch {
+            params: TslParams {
+                w: 2,
+                v: 3,
+                d0: 0,
+                security_bits: 128,
+                tree_height: 2,
+            },
+            statement: Statement {
+                k: 2,
+                ep: 0,
+                m: vec![0; 32],
+                public_keys: Vec::new(),
+                range: None,
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+            },
+            witness: Witness {
+                signatures: vec![signature; 2],
+            },
+        };
+        // Leaf index, randomness, three chain ends, and two path nodes per signature.
+        assert_eq!(
+            individual_signature_bytes(&batch),
+            2 * (4 + 20 + 3 * 28 + 2 * 28)
+        );
+
+        batch.witness.signatures.pop();
+        assert_eq!(individual_signature_bytes(&batch), 4 + 20 + 3 * 28 + 2 * 28);
+    }
+
+    #[test]
+    fn unmeasured_memory_is_recorded_as_null() {
+        let metrics = RunMetrics {
+            prove: Duration::from_micros(7),
+            verify: Duration::from_micros(2),
+            proof_size_bytes: 512,
+            peak_memory_bytes: None,
+        };
+        let line = serde_json::to_string(&RunRecord::new("a.json".into(), &metrics)).unwrap();
+        assert!(line.contains("\"peak_memory_bytes\":null"), "{line}");
+        let record: RunRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(record.metrics(), metrics);
+
+        let measured = RunMetrics {
+            peak_memory_bytes: Some(0),
+            ..metrics
+        };
+        let line = serde_json::to_string(&RunRecord::new("a.json".into(), &measured)).unwrap();
+        assert!(line.contains("\"peak_memory_bytes\":0"), "{line}");
+
+        // Results files written before memory was recorded still load.
+        let old = r#"{"input":"a.json","prove_us":7,"verify_us":2,"proof_size_bytes":512}"#;
+        let record: RunRecord = serde_json::from_str(old).unwrap();
+        assert_eq!(record.metrics(), metrics);
+    }
+
+    #[test]
+    fn summary_averages_proof_size() {
+        let run = |ms, size| RunMetrics {
+            prove: Duration::from_millis(ms),
+            verify: Duration::from_millis(1),
+            proof_size_bytes: size,
+            peak_memory_bytes: None,
+        };
+        let summary = RunSummary::from_runs(&[run(10, 1000), run(30, 3000)]);
+        assert_eq!(summary.runs, 2);
+        assert_eq!(summary.total_prove, Duration::from_millis(40));
+        assert_eq!(summary.total_verify, Duration::from_millis(2));
+        assert_eq!(summary.average_proof_size_bytes, 2000);
+        assert_eq!(RunSummary::from_runs(&[]).average_proof_size_bytes, 0);
+    }
+}
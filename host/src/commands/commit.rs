@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::Path;
+
+use xmss_lib::reference::statement_commitment;
+
+use crate::commands::verify_commitment::commitment_words;
+use crate::commands::CommandResult;
+use crate::utils::decode::batch_from_input_json;
+
+/// Lowercase hex of a 32-byte commitment, most significant byte of the digest first.
+pub fn commitment_hex(commitment: &[u8; 32]) -> String {
+    commitment.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// `commit`: print the statement commitment the guest will reveal for `input`, without
+/// running OpenVM.
+pub fn run(input: &Path) -> CommandResult {
+    let batch = batch_from_input_json(&fs::read_to_string(input)?)?;
+    let commitment = statement_commitment(&batch.statement);
+    println!("0x{}", commitment_hex(&commitment));
+    let words: Vec<String> = commitment_words(&commitment)
+        .iter()
+        .map(|w| format!("0x{w:08x}"))
+        .collect();
+    println!("revealed words 2..=9: {}", words.join(" "));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::input::batch_to_input_json;
+    use xmss_types::{PublicKey, Statement, TslParams, VerificationBatch, Witness};
+
+    #[test]
+    fn input_commitment_matches_guest_vector() {
+        // Same statement as the guest's `commitment_words_reassemble_into_digest`.
+        let batch = VerificationBatch {
+            params: TslParams {
+                w: 2,
+                v: 163,
+                d0: 0,
+                security_bits: 128,
+                tree_height: 18,
+            },
+            statement: Statement {
+                k: 1,
+                ep: 5,
+                m: vec![0x11; 32],
+                public_keys: vec![PublicKey {
+                    root: vec![0x22; 28],
+                    parameter: vec![0x33; 20],
+                    acceptable_roots: Vec::new(),
+                    ed25519_key: None,
+                }],
+                range: None,
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+            },
+            witness: Witness { signatures: vec![] },
+        };
+        let json = batch_to_input_json(&batch).unwrap();
+        let decoded = batch_from_input_json(&json).unwrap();
+        assert_eq!(
+            commitment_hex(&statement_commitment(&decoded.statement)),
+            "e12f1bf6050934bd868a6f12444d6c7a4e60f5284d866e11e0df0f1c15843a66"
+        );
+    }
+}
@@ -1,6 +1,11 @@
 use std::error::Error;
 
+pub mod benchmark_gen;
 pub mod benchmark_openvm;
+pub mod commit;
+pub mod prove_chunks;
+pub mod verify_commitment;
+pub mod verify_reference;
 
 pub use benchmark_openvm::run_default_workflow;
 
@@ -0,0 +1,233 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use xmss_lib::reference::statement_commitment;
+use xmss_types::VerificationBatch;
+
+use crate::commands::verify_commitment::commitment_words;
+use crate::commands::CommandResult;
+use crate::utils::backend::{OpenVmBackend, ProofBackend};
+use crate::utils::decode::batch_from_input_json;
+use crate::utils::input::{batch_to_input_json, split_batch, warn_on_identical_signatures};
+
+/// One chunk of a split batch, ready to prove.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkPlan {
+    pub index: usize,
+    pub range: (u32, u32),
+    pub input_path: PathBuf,
+    pub proof_path: PathBuf,
+    /// Commitment words the chunk's proof is expected to reveal at indices 2..=9.
+    pub commitment: [u32; 8],
+}
+
+/// A chunk plan and the outcome of proving it.
+#[derive(Debug)]
+pub struct ChunkOutcome {
+    pub plan: ChunkPlan,
+    pub result: Result<(), String>,
+}
+
+/// Split `batch` into chunks of at most `max_per_chunk` signatures and write each chunk's
+/// input JSON to `out_dir`.
+pub fn plan_chunks(
+    batch: &VerificationBatch,
+    max_per_chunk: usize,
+    out_dir: &Path,
+) -> Result<Vec<ChunkPlan>, Box<dyn Error>> {
+    let chunks = split_batch(batch, max_per_chunk);
+    if chunks.is_empty() && !batch.witness.signatures.is_empty() {
+        return Err("cannot split batch: chunk size must be at least 1 and the range valid".into());
+    }
+    fs::create_dir_all(out_dir)?;
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let input_path = out_dir.join(format!("chunk-{index}.json"));
+            fs::write(&input_path, batch_to_input_json(chunk)?)?;
+            Ok(ChunkPlan {
+                index,
+                range: chunk.statement.range.unwrap_or((0, chunk.statement.k)),
+                input_path,
+                proof_path: out_dir.join(format!("chunk-{index}.app.proof")),
+                commitment: commitment_words(&statement_commitment(&chunk.statement)),
+            })
+        })
+        .collect()
+}
+
+/// Prove every plan with `prove`, using up to `jobs` worker threads. A failing chunk is
+/// recorded in its outcome and does not stop the others. Outcomes keep plan order.
+pub fn prove_chunks_with<F>(plans: Vec<ChunkPlan>, jobs: usize, prove: F) -> Vec<ChunkOutcome>
+where
+    F: Fn(&ChunkPlan) -> Result<(), String> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(plans.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, plans.len().max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(plan) = plans.get(i) else {
+                    break;
+                };
+                let result = prove(plan);
+                outcomes.lock().unwrap().push(ChunkOutcome {
+                    plan: plan.clone(),
+                    result,
+                });
+            });
+        }
+    });
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by_key(|o| o.plan.index);
+    outcomes
+}
+
+/// Prove one chunk with OpenVM, which must write the proof to the plan's `proof_path`.
+fn prove_with_openvm(plan: &ChunkPlan) -> Result<(), String> {
+    let proof = OpenVmBackend::default()
+        .prove(&plan.input_path)
+        .map_err(|e| e.to_string())?;
+    if proof != plan.proof_path {
+        return Err(format!(
+            "proof written to {}, expected {}",
+            proof.display(),
+            plan.proof_path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// `prove-chunks`: split `input` and prove each chunk, reporting every chunk's outcome.
+pub fn run(input: &Path, max_per_chunk: usize, jobs: usize, out_dir: &Path) -> CommandResult {
+    let batch = batch_from_input_json(&fs::read_to_string(input)?)?;
+    warn_on_identical_signatures(&batch, input);
+    fs::create_dir_all(out_dir)?;
+    // `cargo openvm` runs inside guest/, so hand it absolute paths.
+    let out_dir = fs::canonicalize(out_dir)?;
+    let plans = plan_chunks(&batch, max_per_chunk, &out_dir)?;
+    println!(
+        "Proving {} chunks of up to {} signatures with {} jobs...",
+        plans.len(),
+        max_per_chunk,
+        jobs
+    );
+
+    let outcomes = prove_chunks_with(plans, jobs, prove_with_openvm);
+    let mut failed = 0;
+    for outcome in &outcomes {
+        let plan = &outcome.plan;
+        let commitment: String = plan.commitment.iter().map(|w| format!("{w:08x}")).collect();
+        match &outcome.result {
+            Ok(()) => println!(
+                "chunk {} [{}, {}): {} (commitment words {})",
+                plan.index,
+                plan.range.0,
+                plan.range.1,
+                plan.proof_path.display(),
+                commitment
+            ),
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "chunk {} [{}, {}): FAILED: {}",
+                    plan.index, plan.range.0, plan.range.1, e
+                );
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{failed} of {} chunks failed to prove", outcomes.len()).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xmss_types::{PublicKey, Signature, Statement, TslParams, Witness};
+
+    fn dummy_batch(k: usize) -> VerificationBatch {
+        VerificationBatch {
+            params: TslParams {
+                w: 2,
+                v: 3,
+                d0: 0,
+                security_bits: 128,
+                tree_height: 2,
+            },
+            statement: Statement {
+                k: k as u32,
+                ep: 0,
+                m: vec![0xAB; 32],
+                public_keys: (0..k)
+                    .map(|i| PublicKey {
+                        root: vec![i as u8; 28],
+                        parameter: vec![2; 20],
+                        acceptable_roots: Vec::new(),
+                        ed25519_key: None,
+                    })
+                    .collect(),
+                range: None,
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+            },
+            witness: Witness {
+                signatures: (0..k)
+                    .map(|i| Signature {
+                        leaf_index: 0,
+                        randomness: vec![i as u8; 20],
+                        wots_chain_ends: vec![vec![4; 28]; 3],
+                        auth_path: vec![vec![5; 28]; 2],
+                        auth_path_mask: None,
+                        ed25519_signature: None,
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn split_covers_every_signature_once() {
+        let batch = dummy_batch(5);
+        let chunks = split_batch(&batch, 2);
+        let ranges: Vec<_> = chunks.iter().map(|c| c.statement.range).collect();
+        assert_eq!(ranges, vec![Some((0, 2)), Some((2, 4)), Some((4, 5))]);
+        assert_eq!(chunks[2].witness.signatures, batch.witness.signatures[4..]);
+        assert!(chunks
+            .iter()
+            .all(|c| c.statement.public_keys == batch.statement.public_keys));
+        assert!(split_batch(&batch, 0).is_empty());
+    }
+
+    #[test]
+    fn proves_each_chunk_and_keeps_going_after_a_failure() {
+        let dir = std::env::temp_dir().join(format!("xmss-chunks-{}", std::process::id()));
+        let plans = plan_chunks(&dummy_batch(5), 2, &dir).unwrap();
+        assert_eq!(plans.len(), 3);
+        assert!(plans.iter().all(|p| p.input_path.exists()));
+        assert_ne!(plans[0].commitment, plans[1].commitment);
+
+        let attempted = Mutex::new(Vec::new());
+        let outcomes = prove_chunks_with(plans, 2, |plan| {
+            attempted.lock().unwrap().push(plan.index);
+            if plan.index == 1 {
+                Err("prover crashed".into())
+            } else {
+                Ok(())
+            }
+        });
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(attempted.into_inner().unwrap().len(), 3);
+        let results: Vec<_> = outcomes.iter().map(|o| o.result.is_ok()).collect();
+        assert_eq!(results, vec![true, false, true]);
+    }
+}
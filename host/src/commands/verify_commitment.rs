@@ -0,0 +1,440 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use xmss_lib::reference::{distinct_signers, statement_commitment};
+use xmss_types::{RevealLayout, REVEAL_LAYOUT};
+
+use crate::commands::commit::commitment_hex;
+use crate::commands::CommandResult;
+use crate::utils::decode::{batch_from_input_json, DecodeError};
+
+// Indices come from the guest's `REVEAL_LAYOUT`, so the parser cannot drift from the guest.
+
+/// Public-output index of the `all_valid` flag.
+pub const ALL_VALID_WORD: usize = REVEAL_LAYOUT.all_valid;
+/// Public-output index of the number of signatures checked.
+pub const COUNT_WORD: usize = REVEAL_LAYOUT.count;
+/// Public-output indices holding the statement commitment as little-endian `u32` words.
+pub const COMMITMENT_WORDS: Range<usize> =
+    REVEAL_LAYOUT.commitment..REVEAL_LAYOUT.commitment + RevealLayout::COMMITMENT_WORDS;
+/// Public-output index holding the number of distinct signer roots.
+pub const DISTINCT_SIGNERS_WORD: usize = REVEAL_LAYOUT.distinct_signers;
+/// Public-output index holding the number of witness bytes the guest processed.
+pub const WITNESS_BYTES_WORD: usize = REVEAL_LAYOUT.witness_bytes;
+/// Public-output index of the Poseidon hash-call count revealed by `profiling` guest builds.
+#[cfg(feature = "profiling")]
+pub const HASH_CALLS_WORD: usize = REVEAL_LAYOUT.hash_calls;
+
+/// Read the Poseidon hash-call count, if the words come from a `profiling` guest build.
+#[cfg(feature = "profiling")]
+pub fn hash_calls(words: &[u32]) -> Option<u32> {
+    words.get(HASH_CALLS_WORD).copied()
+}
+
+/// Why an offline commitment check failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitmentError {
+    Input(DecodeError),
+    TooFewWords {
+        expected: usize,
+        actual: usize,
+    },
+    Mismatch {
+        expected: [u32; 8],
+        revealed: [u32; 8],
+    },
+    DistinctSignersMismatch {
+        expected: u32,
+        revealed: u32,
+    },
+    SignaturesInvalid {
+        count: u32,
+    },
+    WitnessBytesMismatch {
+        expected: u32,
+        revealed: u32,
+    },
+}
+
+impl fmt::Display for CommitmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitmentError::Input(err) => write!(f, "could not read input: {err}"),
+            CommitmentError::TooFewWords { expected, actual } => {
+                write!(f, "expected at least {expected} revealed words, got {actual}")
+            }
+            CommitmentError::Mismatch { expected, revealed } => write!(
+                f,
+                "statement commitment mismatch: input commits to {expected:08x?}, proof revealed {revealed:08x?}"
+            ),
+            CommitmentError::DistinctSignersMismatch { expected, revealed } => write!(
+                f,
+                "distinct signer mismatch: input has {expected}, proof revealed {revealed}"
+            ),
+            CommitmentError::SignaturesInvalid { count } => write!(
+                f,
+                "proof attests that not all of its {count} signatures are valid"
+            ),
+            CommitmentError::WitnessBytesMismatch { expected, revealed } => write!(
+                f,
+                "witness size mismatch: input has {expected} bytes, guest processed {revealed}"
+            ),
+        }
+    }
+}
+
+impl Error for CommitmentError {}
+
+impl From<DecodeError> for CommitmentError {
+    fn from(err: DecodeError) -> Self {
+        CommitmentError::Input(err)
+    }
+}
+
+/// Split a 32-byte commitment into the little-endian words the guest reveals.
+pub fn commitment_words(commitment: &[u8; 32]) -> [u32; 8] {
+    let mut words = [0u32; 8];
+    for (word, chunk) in words.iter_mut().zip(commitment.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    words
+}
+
+/// Reassemble a commitment from its revealed words; the inverse of [`commitment_words`].
+pub fn digest_from_words(words: &[u32; 8]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    for (chunk, word) in digest.chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+/// Recompute the statement commitment, distinct-signer count, and witness byte count of
+/// `input_json` and compare them with `revealed_words` (the guest's full public output), without
+/// running OpenVM.
+pub fn verify_commitment_offline(
+    input_json: &str,
+    revealed_words: &[u32],
+) -> Result<(), CommitmentError> {
+    if revealed_words.len() < REVEAL_LAYOUT.words {
+        return Err(CommitmentError::TooFewWords {
+            expected: REVEAL_LAYOUT.words,
+            actual: revealed_words.len(),
+        });
+    }
+    let batch = batch_from_input_json(input_json)?;
+    let revealed: [u32; 8] = revealed_words[COMMITMENT_WORDS].try_into().unwrap();
+    let expected = commitment_words(&statement_commitment(&batch.statement));
+    if revealed != expected {
+        return Err(CommitmentError::Mismatch { expected, revealed });
+    }
+    let expected = distinct_signers(&batch.statement);
+    let revealed = revealed_words[DISTINCT_SIGNERS_WORD];
+    if revealed != expected {
+        return Err(CommitmentError::DistinctSignersMismatch { expected, revealed });
+    }
+    let expected = batch.witness.revealed_bytes();
+    let revealed = revealed_words[WITNESS_BYTES_WORD];
+    if revealed != expected {
+        return Err(CommitmentError::WitnessBytesMismatch { expected, revealed });
+    }
+    Ok(())
+}
+
+/// Fail unless the revealed `all_valid` word is set. A proof can verify while attesting that
+/// some signatures were invalid, so callers that need valid signatures must check this too.
+pub fn require_all_valid(revealed_words: &[u32]) -> Result<(), CommitmentError> {
+    if revealed_words.len() <= COUNT_WORD {
+        return Err(CommitmentError::TooFewWords {
+            expected: COUNT_WORD + 1,
+            actual: revealed_words.len(),
+        });
+    }
+    if revealed_words[ALL_VALID_WORD] == 0 {
+        return Err(CommitmentError::SignaturesInvalid {
+            count: revealed_words[COUNT_WORD],
+        });
+    }
+    Ok(())
+}
+
+/// Parse revealed words separated by commas and/or whitespace; each is decimal or `0x` hex.
+/// Surrounding brackets are ignored so a copied `[a, b, ...]` list works as-is.
+pub fn parse_words(text: &str) -> Result<Vec<u32>, Box<dyn Error>> {
+    text.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let parsed = match token.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => token.parse(),
+            };
+            parsed.map_err(|e| format!("invalid word {token:?}: {e}").into())
+        })
+        .collect()
+}
+
+/// Read the revealed words given inline (`--words`) or from a file (`--words-file`); exactly one
+/// of the two must be set.
+pub fn revealed_words(
+    words: Option<&str>,
+    words_file: Option<&Path>,
+) -> Result<Vec<u32>, Box<dyn Error>> {
+    match (words, words_file) {
+        (Some(text), None) => parse_words(text),
+        (None, Some(path)) => parse_words(&fs::read_to_string(path)?),
+        _ => Err("pass exactly one of --words and --words-file".into()),
+    }
+}
+
+/// `verify-commitment`: check the revealed words, given inline or in a file, against `input`.
+/// With `require_valid`, also fail when the proof attests that some signature was invalid.
+pub fn run(
+    input: &Path,
+    words: Option<&str>,
+    words_file: Option<&Path>,
+    require_valid: bool,
+) -> CommandResult {
+    let input_json = fs::read_to_string(input)?;
+    let words = revealed_words(words, words_file)?;
+    verify_commitment_offline(&input_json, &words)?;
+    if require_valid {
+        require_all_valid(&words)?;
+    }
+    let revealed: [u32; 8] = words[COMMITMENT_WORDS].try_into().unwrap();
+    println!(
+        "Statement commitment 0x{} matches {}",
+        commitment_hex(&digest_from_words(&revealed)),
+        input.display()
+    );
+    #[cfg(feature = "profiling")]
+    if let Some(calls) = hash_calls(&words) {
+        println!("Poseidon hash calls: {calls}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::input::batch_to_input_json;
+    use xmss_types::{PublicKey, Signature, Statement, TslParams, VerificationBatch, Witness};
+
+    fn empty_batch_json() -> (String, VerificationBatch) {
+        let batch = VerificationBatch {
+            params: TslParams {
+                w: 2,
+                v: 163,
+                d0: 0,
+                security_bits: 128,
+                tree_height: 18,
+            },
+            statement: Statement {
+                k: 0,
+                ep: 3,
+                m: vec![0x5A; 32],
+                public_keys: Vec::new(),
+                range: None,
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+            },
+            witness: Witness {
+                signatures: Vec::new(),
+            },
+        };
+        (batch_to_input_json(&batch).unwrap(), batch)
+    }
+
+    /// The words a guest proving `batch` with every signature valid would reveal.
+    fn guest_words(batch: &VerificationBatch) -> Vec<u32> {
+        let mut words = vec![0u32; REVEAL_LAYOUT.words];
+        words[ALL_VALID_WORD] = 1;
+        words[COUNT_WORD] = batch.witness.signatures.len() as u32;
+        words[COMMITMENT_WORDS]
+            .copy_from_slice(&commitment_words(&statement_commitment(&batch.statement)));
+        words[DISTINCT_SIGNERS_WORD] = distinct_signers(&batch.statement);
+        words[WITNESS_BYTES_WORD] = batch.witness.revealed_bytes();
+        words
+    }
+
+    #[test]
+    fn matching_words_pass_and_mismatching_words_fail() {
+        let (json, batch) = empty_batch_json();
+        let words = guest_words(&batch);
+        assert_eq!(verify_commitment_offline(&json, &words), Ok(()));
+
+        let mut tampered = words.clone();
+        tampered[5] ^= 1;
+        assert!(matches!(
+            verify_commitment_offline(&json, &tampered),
+            Err(CommitmentError::Mismatch { .. })
+        ));
+        // Dropping the trailing words skips no check; the output is rejected as too short.
+        assert_eq!(
+            verify_commitment_offline(&json, &words[..WITNESS_BYTES_WORD]),
+            Err(CommitmentError::TooFewWords {
+                expected: REVEAL_LAYOUT.words,
+                actual: WITNESS_BYTES_WORD
+            })
+        );
+    }
+
+    #[test]
+    fn require_valid_rejects_proof_of_invalid_signatures() {
+        let (json, batch) = empty_batch_json();
+        let mut words = guest_words(&batch);
+        words[ALL_VALID_WORD] = 0;
+        words[COUNT_WORD] = 3;
+        // The words are consistent with the input; only the validity flag is unset.
+        assert_eq!(verify_commitment_offline(&json, &words), Ok(()));
+        assert_eq!(
+            require_all_valid(&words),
+            Err(CommitmentError::SignaturesInvalid { count: 3 })
+        );
+
+        words[ALL_VALID_WORD] = 1;
+        assert_eq!(require_all_valid(&words), Ok(()));
+        assert!(matches!(
+            require_all_valid(&[1]),
+            Err(CommitmentError::TooFewWords { .. })
+        ));
+    }
+
+    #[test]
+    fn revealed_distinct_signers_must_match_input() {
+        let (json, batch) = empty_batch_json();
+        let mut words = guest_words(&batch);
+        words[DISTINCT_SIGNERS_WORD] = 2;
+        assert_eq!(
+            verify_commitment_offline(&json, &words),
+            Err(CommitmentError::DistinctSignersMismatch {
+                expected: 0,
+                revealed: 2
+            })
+        );
+    }
+
+    #[test]
+    fn commitment_words_round_trip_in_guest_byte_order() {
+        // Same statement and words as the guest's `commitment_words_reassemble_into_digest`.
+        let statement = Statement {
+            k: 1,
+            ep: 5,
+            m: vec![0x11; 32],
+            public_keys: vec![PublicKey {
+                root: vec![0x22; 28],
+                parameter: vec![0x33; 20],
+                acceptable_roots: Vec::new(),
+                ed25519_key: None,
+            }],
+            range: None,
+            allow_extra_public_keys: false,
+            epoch_window: None,
+            randomness_commitment: None,
+            hybrid_ed25519: false,
+        };
+        let digest = statement_commitment(&statement);
+        let words = commitment_words(&digest);
+        assert_eq!(
+            words,
+            [
+                0xf61b_2fe1,
+                0xbd34_0905,
+                0x126f_8a86,
+                0x7a6c_4d44,
+                0x28f5_604e,
+                0x116e_864d,
+                0x1c0f_dfe0,
+                0x663a_8415,
+            ]
+        );
+        assert_eq!(digest_from_words(&words), digest);
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn reads_hash_calls_word_when_present() {
+        let mut words = vec![0u32; HASH_CALLS_WORD];
+        assert_eq!(hash_calls(&words), None);
+        words.push(4242);
+        assert_eq!(hash_calls(&words), Some(4242));
+    }
+
+    #[test]
+    fn parser_reads_within_the_guest_output() {
+        let (json, batch) = empty_batch_json();
+        let words = guest_words(&batch);
+        assert_eq!(verify_commitment_offline(&json, &words), Ok(()));
+        assert_eq!(require_all_valid(&words), Ok(()));
+        assert!(DISTINCT_SIGNERS_WORD < REVEAL_LAYOUT.words);
+        assert!(COMMITMENT_WORDS.end <= REVEAL_LAYOUT.words);
+    }
+
+    #[test]
+    fn revealed_witness_bytes_must_match_input() {
+        let (_, mut batch) = empty_batch_json();
+        let signature = Signature {
+            leaf_index: 0,
+            randomness: vec![1; 20],
+            wots_chain_ends: vec![vec![2; 28]; 3],
+            auth_path: vec![vec![3; 28]; 2],
+            auth_path_mask: None,
+            ed25519_signature: None,
+        };
+        batch.witness.signatures = vec![signature; 2];
+        let json = batch_to_input_json(&batch).unwrap();
+        let mut words = guest_words(&batch);
+        assert_eq!(verify_commitment_offline(&json, &words), Ok(()));
+
+        // A guest that read only part of the witness reveals fewer bytes.
+        let last = batch.witness.signatures[1].auth_path.last().unwrap().len() as u32;
+        words[WITNESS_BYTES_WORD] -= last;
+        assert_eq!(
+            verify_commitment_offline(&json, &words),
+            Err(CommitmentError::WitnessBytesMismatch {
+                expected: batch.witness.revealed_bytes(),
+                revealed: batch.witness.revealed_bytes() - last,
+            })
+        );
+    }
+
+    #[test]
+    fn inline_words_are_checked_without_a_file() {
+        let (json, batch) = empty_batch_json();
+        let words = guest_words(&batch);
+        let inline = words
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        let parsed = revealed_words(Some(&inline), None).unwrap();
+        assert_eq!(verify_commitment_offline(&json, &parsed), Ok(()));
+
+        let short = revealed_words(Some("1,0,7"), None).unwrap();
+        assert_eq!(
+            verify_commitment_offline(&json, &short),
+            Err(CommitmentError::TooFewWords {
+                expected: REVEAL_LAYOUT.words,
+                actual: 3
+            })
+        );
+        assert!(revealed_words(None, None).is_err());
+        assert!(revealed_words(Some("1"), Some(Path::new("revealed.txt"))).is_err());
+    }
+
+    #[test]
+    fn parses_decimal_and_hex_words() {
+        assert_eq!(
+            parse_words("[1, 0x10,\n 4294967295]").unwrap(),
+            vec![1, 16, u32::MAX]
+        );
+        assert!(parse_words("1, two").is_err());
+    }
+}
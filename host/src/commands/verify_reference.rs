@@ -0,0 +1,34 @@
+use std::fs;
+use std::path::Path;
+
+use xmss_lib::reference::{ed25519_signatures_valid, verify_parallel};
+use xmss_lib::run_with_threads;
+
+use crate::commands::CommandResult;
+use crate::utils::decode::batch_from_input_json;
+
+/// `verify-reference`: check every signature of `input` with hash-sig on the host, using at
+/// most `threads` worker threads (all cores when unset). Hybrid statements also need valid
+/// Ed25519 signatures, which proofs do not attest.
+pub fn run(input: &Path, threads: Option<usize>) -> CommandResult {
+    let batch = batch_from_input_json(&fs::read_to_string(input)?)?;
+    let (valid, elapsed) = run_with_threads(threads, || verify_parallel(&batch))?;
+    let valid = valid && ed25519_signatures_valid(&batch);
+    let signatures = batch.witness.signatures.len();
+    if !valid {
+        return Err(format!(
+            "{}: batch of {} signatures is invalid ({:?})",
+            input.display(),
+            signatures,
+            elapsed
+        )
+        .into());
+    }
+    println!(
+        "{}: all {} signatures valid ({:?})",
+        input.display(),
+        signatures,
+        elapsed
+    );
+    Ok(())
+}
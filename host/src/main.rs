@@ -1,8 +1,150 @@
 use std::error::Error;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
 
 mod commands;
 mod utils;
 
+use utils::input::{BatchInputOptions, InjectedFailure};
+
+/// Prove and verify batches of XMSS signatures with OpenVM.
+///
+/// Without a subcommand, runs the default generate/prove/verify benchmark.
+#[derive(Parser)]
+#[command(name = "xmss-host")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prove and verify existing inputs; without `--input-dir`, same as running with no
+    /// subcommand.
+    BenchmarkOpenvm {
+        /// Directory whose `*.json` inputs are each proven and verified.
+        #[arg(long)]
+        input_dir: Option<PathBuf>,
+        /// Append each completed run to this JSON-lines file as it finishes.
+        #[arg(long, requires = "input_dir")]
+        json: Option<PathBuf>,
+        /// Skip inputs already recorded in the `--json` file instead of starting over.
+        #[arg(long, requires = "json")]
+        resume: bool,
+        /// Generate an input with this defect (bad-chain-count, bad-auth-len, wrong-root,
+        /// bad-index, bad-params) to test failure handling end to end.
+        #[arg(long, hide = true, conflicts_with = "input_dir")]
+        inject_failure: Option<InjectedFailure>,
+        /// First epoch the generated keys are active for.
+        #[arg(long, default_value_t = 0, conflicts_with = "input_dir")]
+        activation_epoch: u32,
+        /// Epoch the generated signatures are made at; must not precede `--activation-epoch`.
+        #[arg(long, default_value_t = 0, conflicts_with = "input_dir")]
+        signing_epoch: u32,
+    },
+    /// Time input generation alone, without proving.
+    BenchmarkGen {
+        /// Number of signatures per generated batch.
+        #[arg(long, default_value_t = 2)]
+        signatures: usize,
+        /// Number of generation runs to average over.
+        #[arg(long, default_value_t = 3)]
+        iterations: u32,
+    },
+    /// Split an input into signature-range chunks and prove each chunk separately.
+    ProveChunks {
+        /// Input JSON in the `cargo openvm --input` format.
+        #[arg(long)]
+        input: PathBuf,
+        /// Maximum number of signatures per chunk.
+        #[arg(long)]
+        max_per_chunk: usize,
+        /// Number of chunks to prove concurrently.
+        #[arg(long, default_value_t = 1)]
+        jobs: usize,
+        /// Directory for chunk inputs and proofs.
+        #[arg(long, default_value = "chunks")]
+        out_dir: PathBuf,
+    },
+    /// Verify an input's signatures with hash-sig on the host, without proving.
+    VerifyReference {
+        /// Input JSON in the `cargo openvm --input` format.
+        #[arg(long)]
+        input: PathBuf,
+        /// Worker threads for parallel verification (default: all cores).
+        #[arg(long)]
+        threads: Option<usize>,
+    },
+    /// Print the statement commitment of an input JSON, without running OpenVM.
+    Commit {
+        /// Input JSON in the `cargo openvm --input` format.
+        #[arg(long)]
+        input: PathBuf,
+    },
+    /// Check revealed public-output words against the statement commitment of an input JSON,
+    /// without running OpenVM.
+    VerifyCommitment {
+        /// Input JSON in the `cargo openvm --input` format.
+        #[arg(long)]
+        input: PathBuf,
+        /// File with the revealed words (decimal or 0x hex, comma/whitespace separated).
+        #[arg(long, required_unless_present = "words", conflicts_with = "words")]
+        words_file: Option<PathBuf>,
+        /// Revealed words given inline instead of in a file, e.g. `--words 1,0,0x66e3...`.
+        #[arg(long)]
+        words: Option<String>,
+        /// Also fail unless the revealed `all_valid` word is set.
+        #[arg(long)]
+        require_valid: bool,
+    },
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
-    commands::run_default_workflow()
+    match Cli::parse().command {
+        None => commands::run_default_workflow(&BatchInputOptions::default()),
+        Some(Command::BenchmarkOpenvm {
+            input_dir: None,
+            inject_failure,
+            activation_epoch,
+            signing_epoch,
+            ..
+        }) => commands::run_default_workflow(&BatchInputOptions {
+            activation_epoch,
+            signing_epoch,
+            inject_failure,
+            ..BatchInputOptions::default()
+        }),
+        Some(Command::BenchmarkOpenvm {
+            input_dir: Some(dir),
+            json,
+            resume,
+            ..
+        }) => commands::benchmark_openvm::run_input_dir(&dir, json.as_deref(), resume),
+        Some(Command::BenchmarkGen {
+            signatures,
+            iterations,
+        }) => commands::benchmark_gen::run(signatures, iterations),
+        Some(Command::ProveChunks {
+            input,
+            max_per_chunk,
+            jobs,
+            out_dir,
+        }) => commands::prove_chunks::run(&input, max_per_chunk, jobs, &out_dir),
+        Some(Command::VerifyReference { input, threads }) => {
+            commands::verify_reference::run(&input, threads)
+        }
+        Some(Command::Commit { input }) => commands::commit::run(&input),
+        Some(Command::VerifyCommitment {
+            input,
+            words_file,
+            words,
+            require_valid,
+        }) => commands::verify_commitment::run(
+            &input,
+            words.as_deref(),
+            words_file.as_deref(),
+            require_valid,
+        ),
+    }
 }
@@ -0,0 +1,48 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use super::openvm::run_in_guest;
+
+type DynError = Box<dyn Error>;
+
+/// A proving system the host commands can prove inputs and check proofs with.
+pub trait ProofBackend {
+    /// Prove `input` (an absolute input JSON path) and return the path of the written proof.
+    fn prove(&self, input: &Path) -> Result<PathBuf, DynError>;
+
+    /// Check the proof at `proof`, failing if it does not verify.
+    fn verify(&self, proof: &Path) -> Result<(), DynError>;
+}
+
+/// Proves with `cargo openvm` in the guest crate at `guest_dir` (guest/ under the working
+/// directory by default). Proofs are written next to their input as `<input>.app.proof`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenVmBackend {
+    pub guest_dir: PathBuf,
+}
+
+impl Default for OpenVmBackend {
+    fn default() -> Self {
+        OpenVmBackend {
+            guest_dir: PathBuf::from("guest"),
+        }
+    }
+}
+
+impl ProofBackend for OpenVmBackend {
+    fn prove(&self, input: &Path) -> Result<PathBuf, DynError> {
+        let proof = input.with_extension("app.proof");
+        let input_str = input.to_str().ok_or("input path is not valid UTF-8")?;
+        let proof_str = proof.to_str().ok_or("proof path is not valid UTF-8")?;
+        run_in_guest(
+            &self.guest_dir,
+            ["prove", "app", "--input", input_str, "--proof", proof_str],
+        )?;
+        Ok(proof)
+    }
+
+    fn verify(&self, proof: &Path) -> Result<(), DynError> {
+        let proof_str = proof.to_str().ok_or("proof path is not valid UTF-8")?;
+        run_in_guest(&self.guest_dir, ["verify", "app", "--proof", proof_str])
+    }
+}
@@ -0,0 +1,268 @@
+use std::error::Error;
+use std::fmt;
+
+use xmss_types::VerificationBatch;
+
+/// Marker byte that starts every input entry we write: `cargo openvm` reads a `0x01` entry as
+/// raw bytes. Entries with any other marker use a different encoding and are rejected.
+pub const INPUT_MARKER: u8 = 0x01;
+
+/// Input layouts, identified by the marker byte that starts an entry. The decoder dispatches on
+/// it so a changed layout can get its own marker while older inputs keep decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputVersion {
+    /// [`INPUT_MARKER`]: OpenVM serde words of a [`VerificationBatch`], the layout written today.
+    V1,
+    /// `0x02`: reserved for the next layout; recognised but not decodable yet.
+    V2,
+}
+
+impl InputVersion {
+    fn from_marker(marker: u8) -> Option<Self> {
+        match marker {
+            INPUT_MARKER => Some(InputVersion::V1),
+            0x02 => Some(InputVersion::V2),
+            _ => None,
+        }
+    }
+}
+
+/// Reasons a word stream could not be decoded into a [`VerificationBatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    Empty,
+    Malformed(String),
+    MissingInput,
+    InvalidHex(String),
+    UnsupportedMarker(u8),
+    UnsupportedVersion(InputVersion),
+    TrailingWords(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Empty => write!(f, "no input words to decode"),
+            DecodeError::Malformed(msg) => write!(f, "malformed verification batch: {msg}"),
+            DecodeError::MissingInput => write!(f, "input JSON has no 0x-prefixed input entry"),
+            DecodeError::InvalidHex(msg) => write!(f, "invalid input hex: {msg}"),
+            DecodeError::UnsupportedMarker(marker) => write!(
+                f,
+                "unsupported input marker 0x{marker:02x} (expected 0x{INPUT_MARKER:02x})"
+            ),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported input version {version:?}")
+            }
+            DecodeError::TrailingWords(count) => {
+                write!(f, "{count} unexpected words after the verification batch")
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Decode a [`VerificationBatch`] from OpenVM serde words. Truncated or garbage input is
+/// reported as an error rather than a panic, so arbitrary slices can be fed in (e.g. fuzzing).
+pub fn try_batch_from_words(words: &[u32]) -> Result<VerificationBatch, DecodeError> {
+    if words.is_empty() {
+        return Err(DecodeError::Empty);
+    }
+    openvm::serde::from_slice(words).map_err(|e| DecodeError::Malformed(e.to_string()))
+}
+
+/// [`try_batch_from_words`] that also rejects words left over after the batch, so padding cannot
+/// ride along unnoticed. The lenient decoder stays available for streams that append data a
+/// newer writer knows about.
+pub fn try_batch_from_words_strict(words: &[u32]) -> Result<VerificationBatch, DecodeError> {
+    let batch = try_batch_from_words(words)?;
+    // The encoding is canonical, so re-encoding gives exactly the words the batch consumed.
+    let consumed = openvm::serde::to_vec(&batch)
+        .map_err(|e| DecodeError::Malformed(e.to_string()))?
+        .len();
+    match words.len() - consumed {
+        0 => Ok(batch),
+        extra => Err(DecodeError::TrailingWords(extra)),
+    }
+}
+
+/// Decode the batch stored in a `cargo openvm --input` JSON file, as written by
+/// [`batch_to_input_json`](super::input::batch_to_input_json). Decoding is strict: words after
+/// the batch are an error.
+pub fn batch_from_input_json(json: &str) -> Result<VerificationBatch, DecodeError> {
+    match input_json_words(json)? {
+        (InputVersion::V1, words) => try_batch_from_words_strict(&words),
+        (version @ InputVersion::V2, _) => Err(DecodeError::UnsupportedVersion(version)),
+    }
+}
+
+/// Layout version and words of the first `0x`-prefixed entry in an input JSON file
+/// (little-endian hex words), with the marker byte stripped.
+fn input_json_words(json: &str) -> Result<(InputVersion, Vec<u32>), DecodeError> {
+    let start = json.find("\"0x").ok_or(DecodeError::MissingInput)? + 3;
+    let len = json[start..].find('"').ok_or(DecodeError::MissingInput)?;
+    let entry = &json[start..start + len];
+    let marker = entry
+        .get(..2)
+        .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+        .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+        .ok_or_else(|| DecodeError::InvalidHex("entry has no marker byte".into()))?;
+    let version =
+        InputVersion::from_marker(marker).ok_or(DecodeError::UnsupportedMarker(marker))?;
+    let hex = &entry[2..];
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(DecodeError::InvalidHex("non-hex character".into()));
+    }
+    if hex.len() % 8 != 0 {
+        return Err(DecodeError::InvalidHex(format!(
+            "{} hex digits is not a whole number of words",
+            hex.len()
+        )));
+    }
+    let words = hex
+        .as_bytes()
+        .chunks_exact(8)
+        .map(|word| {
+            let mut bytes = [0u8; 4];
+            for (byte, pair) in bytes.iter_mut().zip(word.chunks_exact(2)) {
+                // Both digits were checked above, so this cannot fail.
+                *byte = u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap();
+            }
+            u32::from_le_bytes(bytes)
+        })
+        .collect();
+    Ok((version, words))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::input::batch_to_input_json;
+    use xmss_types::{PublicKey, Signature, Statement, TslParams, Witness};
+
+    fn small_batch() -> VerificationBatch {
+        VerificationBatch {
+            params: TslParams {
+                w: 2,
+                v: 3,
+                d0: 0,
+                security_bits: 128,
+                tree_height: 2,
+            },
+            statement: Statement {
+                k: 1,
+                ep: 7,
+                m: vec![0xAB; 32],
+                public_keys: vec![PublicKey {
+                    root: vec![1; 28],
+                    parameter: vec![2; 20],
+                    acceptable_roots: Vec::new(),
+                    ed25519_key: None,
+                }],
+                range: None,
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+            },
+            witness: Witness {
+                signatures: vec![Signature {
+                    leaf_index: 7,
+                    randomness: vec![3; 20],
+                    wots_chain_ends: vec![vec![4; 28]; 3],
+                    auth_path: vec![vec![5; 28]; 2],
+                    auth_path_mask: None,
+                    ed25519_signature: None,
+                }],
+            },
+        }
+    }
+
+    #[test]
+    fn decodes_serialized_batch() {
+        let batch = small_batch();
+        let words: Vec<u32> = openvm::serde::to_vec(&batch).unwrap();
+        assert_eq!(try_batch_from_words(&words), Ok(batch));
+    }
+
+    #[test]
+    fn decodes_generated_input_json() {
+        let batch = small_batch();
+        let json = batch_to_input_json(&batch).unwrap();
+        assert_eq!(batch_from_input_json(&json), Ok(batch));
+
+        assert_eq!(
+            batch_from_input_json("{\"input\": []}"),
+            Err(DecodeError::MissingInput)
+        );
+        assert!(matches!(
+            batch_from_input_json("{\"input\": [\"0x01abc\"]}"),
+            Err(DecodeError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_input_marker() {
+        let json = batch_to_input_json(&small_batch()).unwrap();
+        let remarked = json.replacen("\"0x01", "\"0x7f", 1);
+        let err = batch_from_input_json(&remarked).unwrap_err();
+        assert_eq!(err, DecodeError::UnsupportedMarker(0x7f));
+        assert_eq!(
+            err.to_string(),
+            "unsupported input marker 0x7f (expected 0x01)"
+        );
+        assert!(matches!(
+            batch_from_input_json("{\"input\": [\"0x\"]}"),
+            Err(DecodeError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn dispatches_on_input_version() {
+        let json = batch_to_input_json(&small_batch()).unwrap();
+        assert_eq!(batch_from_input_json(&json), Ok(small_batch()));
+
+        let v2 = json.replacen("\"0x01", "\"0x02", 1);
+        let err = batch_from_input_json(&v2).unwrap_err();
+        assert_eq!(err, DecodeError::UnsupportedVersion(InputVersion::V2));
+        assert_eq!(err.to_string(), "unsupported input version V2");
+    }
+
+    #[test]
+    fn strict_decoding_rejects_trailing_words() {
+        let batch = small_batch();
+        let mut words: Vec<u32> = openvm::serde::to_vec(&batch).unwrap();
+        assert_eq!(try_batch_from_words_strict(&words), Ok(batch.clone()));
+
+        words.extend([0, 0xdead_beef]);
+        assert_eq!(
+            try_batch_from_words_strict(&words),
+            Err(DecodeError::TrailingWords(2))
+        );
+        assert_eq!(try_batch_from_words(&words), Ok(batch));
+    }
+
+    #[test]
+    fn truncated_words_are_an_error() {
+        let words: Vec<u32> = openvm::serde::to_vec(&small_batch()).unwrap();
+        assert_eq!(try_batch_from_words(&[]), Err(DecodeError::Empty));
+        for len in [1, 5, words.len() / 2, words.len() - 1] {
+            assert!(try_batch_from_words(&words[..len]).is_err(), "len {len}");
+        }
+    }
+
+    #[test]
+    fn garbage_words_are_an_error() {
+        assert!(try_batch_from_words(&[u32::MAX; 64]).is_err());
+        let mut state = 0x1234_5678u32;
+        let noise: Vec<u32> = (0..256)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state
+            })
+            .collect();
+        assert!(try_batch_from_words(&noise).is_err());
+    }
+}
@@ -1,17 +1,21 @@
 use std::error::Error;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use rand::SeedableRng;
 use xmss_lib::{
     hash_message_to_digest,
     hashsig_export::{
-        export_public_key, export_signature, HashsigExportError, WINTERNITZ_TREE_HEIGHT,
+        export_public_key, export_signature, PoseidonConfig, WINTERNITZ_TREE_HEIGHT,
         WINTERNITZ_W1_NUM_CHAINS,
     },
-    validate_epoch_range, SIGWinternitzLifetime18W1, SignatureScheme,
+    reference, validate_epoch_range, SIGWinternitzLifetime18W1, SignatureScheme,
 };
-use xmss_types::{PublicKey, Signature, Statement, TslParams, VerificationBatch, Witness};
+use xmss_types::{PublicKey, Statement, TslParams, VerificationBatch, Witness};
+
+use super::decode::{try_batch_from_words, INPUT_MARKER};
+use super::keys::{self, KeyParams};
 
 fn to_hex(bytes: &[u8]) -> String {
     const HEX: &[u8; 16] = b"0123456789abcdef";
@@ -23,9 +27,182 @@ fn to_hex(bytes: &[u8]) -> String {
     s
 }
 
+/// Probe value serialized before every input generation: a `u32` followed by a `u64`.
+const WORD_ENCODING_PROBE: (u32, u64) = (0x0403_0201, 0x0c0b_0a09_0807_0605);
+/// Bytes the guest expects for [`WORD_ENCODING_PROBE`]: one word for the `u32`, then the `u64`
+/// as low word followed by high word, each word written little-endian.
+const WORD_ENCODING_PROBE_BYTES: [u8; 12] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+/// Seed of the first cached key; key `i` of a batch uses this plus `i`.
+const KEY_CACHE_SEED: u64 = 0x5EED_0000;
+
+fn words_to_le_bytes(words: &[u32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(words.len() * 4);
+    for w in words {
+        bytes.extend_from_slice(&w.to_le_bytes());
+    }
+    bytes
+}
+
+/// Fail fast when `openvm::serde` no longer produces the little-endian word layout the guest
+/// decodes (e.g. after an OpenVM upgrade that changes the encoding).
+fn check_word_encoding() -> Result<(), Box<dyn Error>> {
+    let words: Vec<u32> = openvm::serde::to_vec(&WORD_ENCODING_PROBE)?;
+    let bytes = words_to_le_bytes(&words);
+    if bytes != WORD_ENCODING_PROBE_BYTES {
+        return Err(format!(
+            "unexpected OpenVM word encoding: probe serialized to {} (expected {})",
+            to_hex(&bytes),
+            to_hex(&WORD_ENCODING_PROBE_BYTES)
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Options for [`generate_batch_input_with`]. The defaults match [`generate_batch_input`].
+#[derive(Debug, Clone, Default)]
+pub struct BatchInputOptions {
+    /// Skip the hash-sig smoke test (re-verifying every freshly generated signature before it
+    /// is exported). The output is identical; only the sanity check is dropped.
+    pub skip_smoke_test: bool,
+    /// Fail before generating anything when the estimated serialized input exceeds this many
+    /// words, instead of letting the guest run out of memory. `None` disables the check.
+    pub max_input_words: Option<usize>,
+    /// Run the host reference verifier on the exported batch and fail, before writing it,
+    /// unless every signature verifies. Catches export bugs before a costly prove.
+    pub validate: bool,
+    /// First epoch each generated key is active for.
+    pub activation_epoch: u32,
+    /// Epoch every signature is made at, and so the leaf it uses; must not precede
+    /// `activation_epoch`. Non-zero epochs exercise other leaf indices and Merkle paths.
+    pub signing_epoch: u32,
+    /// Reuse key pairs cached in this directory (e.g. `./tmp`) across runs instead of
+    /// regenerating them. Each key then comes from its own seed, so the keys differ from an
+    /// uncached run but are identical between cached ones.
+    pub key_cache_dir: Option<PathBuf>,
+    /// Corrupt the generated batch so the guest must reject it, for testing the pipeline's
+    /// failure handling. Applied after the `validate` check.
+    pub inject_failure: Option<InjectedFailure>,
+}
+
+/// A defect [`BatchInputOptions::inject_failure`] plants in the first signature or its key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectedFailure {
+    /// Drop one WOTS chain end.
+    BadChainCount,
+    /// Drop the top authentication path node.
+    BadAuthLen,
+    /// Flip a bit of the public key root.
+    WrongRoot,
+    /// Use a leaf index other than the one the epoch signs with.
+    BadIndex,
+    /// Declare parameters other than the instantiation the guest verifies.
+    BadParams,
+}
+
+impl InjectedFailure {
+    pub const ALL: [InjectedFailure; 5] = [
+        InjectedFailure::BadChainCount,
+        InjectedFailure::BadAuthLen,
+        InjectedFailure::WrongRoot,
+        InjectedFailure::BadIndex,
+        InjectedFailure::BadParams,
+    ];
+
+    /// Name accepted on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            InjectedFailure::BadChainCount => "bad-chain-count",
+            InjectedFailure::BadAuthLen => "bad-auth-len",
+            InjectedFailure::WrongRoot => "wrong-root",
+            InjectedFailure::BadIndex => "bad-index",
+            InjectedFailure::BadParams => "bad-params",
+        }
+    }
+
+    fn apply(self, batch: &mut VerificationBatch) {
+        let sig = &mut batch.witness.signatures[0];
+        match self {
+            InjectedFailure::BadChainCount => {
+                sig.wots_chain_ends.pop();
+            }
+            InjectedFailure::BadAuthLen => {
+                sig.auth_path.pop();
+            }
+            InjectedFailure::WrongRoot => batch.statement.public_keys[0].root[0] ^= 1,
+            InjectedFailure::BadIndex => sig.leaf_index ^= 1,
+            InjectedFailure::BadParams => batch.params.v += 1,
+        }
+    }
+}
+
+impl FromStr for InjectedFailure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        InjectedFailure::ALL
+            .into_iter()
+            .find(|failure| failure.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = InjectedFailure::ALL.iter().map(|f| f.name()).collect();
+                format!(
+                    "unknown failure {s:?}; expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Exact `openvm::serde` word count of a generated input with `signatures` entries: each scalar
+/// takes one word (the `u64` epoch two), and each byte vector a length word plus one per byte.
+pub fn estimated_input_words(signatures: usize) -> usize {
+    let bytes = |len: usize| 1 + len;
+    let node = bytes(PoseidonConfig::DEFAULT.node_bytes());
+    let params = 5;
+    // k, ep, m, public-key count, range (None), allow_extra_public_keys, epoch_window (None),
+    // randomness_commitment (None), hybrid_ed25519
+    let statement = 1 + 2 + bytes(32) + 1 + 1 + 1 + 1 + 1 + 1;
+    // root, parameter, acceptable_roots (empty), ed25519_key (None)
+    let public_key = node + bytes(PoseidonConfig::DEFAULT.parameter_bytes()) + 1 + 1;
+    // leaf_index, randomness, chain ends, auth path, auth_path_mask (None),
+    // ed25519_signature (None)
+    let signature = 1
+        + bytes(PoseidonConfig::DEFAULT.randomness_bytes())
+        + 1
+        + WINTERNITZ_W1_NUM_CHAINS * node
+        + 1
+        + WINTERNITZ_TREE_HEIGHT * node
+        + 1
+        + 1;
+    params + statement + signatures * public_key + 1 + signatures * signature
+}
+
 /// Generate a batch input JSON with the requested number of signatures.
 /// This creates structurally valid, dummy signatures/keys suitable for benchmarking.
 pub fn generate_batch_input(signatures: usize, out_path: &str) -> Result<(), Box<dyn Error>> {
+    generate_batch_input_with(signatures, out_path, &BatchInputOptions::default())
+}
+
+/// Same as [`generate_batch_input`], with explicit [`BatchInputOptions`].
+pub fn generate_batch_input_with(
+    signatures: usize,
+    out_path: &str,
+    options: &BatchInputOptions,
+) -> Result<(), Box<dyn Error>> {
+    check_word_encoding()?;
+
+    if let Some(max_words) = options.max_input_words {
+        let estimated = estimated_input_words(signatures);
+        if estimated > max_words {
+            return Err(format!(
+                "a batch of {signatures} signatures serializes to about {estimated} words, \
+                 exceeding the {max_words}-word limit; split it into smaller batches \
+                 (e.g. with Statement.range) and prove them separately"
+            )
+            .into());
+        }
+    }
+
     let params = TslParams {
         w: 2,
         v: WINTERNITZ_W1_NUM_CHAINS as u16,
@@ -36,38 +213,51 @@ pub fn generate_batch_input(signatures: usize, out_path: &str) -> Result<(), Box
 
     let digest = hash_message_to_digest(b"bench");
     let mut rng = rand::rngs::StdRng::seed_from_u64(0xBAD5EED);
-    let epoch: u32 = 0;
+    let epoch = options.signing_epoch;
+    let activation_epoch = options.activation_epoch as usize;
+    // Keys stay active from their activation epoch up to and including the signing epoch.
+    let num_active_epochs = (epoch as usize).saturating_sub(activation_epoch) + 1;
+    validate_epoch_range(activation_epoch, num_active_epochs, epoch)?;
+    if epoch as usize >= 1 << WINTERNITZ_TREE_HEIGHT {
+        return Err(format!("signing epoch {epoch} is past the key lifetime").into());
+    }
 
     let mut public_keys = Vec::with_capacity(signatures);
     let mut signatures_vec = Vec::with_capacity(signatures);
 
-    for _ in 0..signatures {
-        let activation_epoch = epoch as usize;
-        let num_active_epochs = 1usize;
-        let (pk, sk) =
-            SIGWinternitzLifetime18W1::key_gen(&mut rng, activation_epoch, num_active_epochs);
-        validate_epoch_range(activation_epoch, num_active_epochs, epoch)?;
+    for i in 0..signatures {
+        let (pk, sk) = match &options.key_cache_dir {
+            Some(dir) => keys::cached_keygen(
+                dir,
+                KEY_CACHE_SEED.wrapping_add(i as u64),
+                KeyParams {
+                    activation_epoch,
+                    num_active_epochs,
+                },
+            )?,
+            None => {
+                SIGWinternitzLifetime18W1::key_gen(&mut rng, activation_epoch, num_active_epochs)
+            }
+        };
         let sig = SIGWinternitzLifetime18W1::sign(&mut rng, &sk, epoch, &digest)
             .map_err(|e| format!("hash-sig signing failed: {e}"))?;
 
-        if !SIGWinternitzLifetime18W1::verify(&pk, epoch, &digest, &sig) {
+        if !options.skip_smoke_test && !SIGWinternitzLifetime18W1::verify(&pk, epoch, &digest, &sig)
+        {
             return Err("hash-sig verification failed for generated sample".into());
         }
 
-        let exported_pk = export_public_key(&pk).map_err(export_err)?;
-        let exported_sig = export_signature(&sig).map_err(export_err)?;
+        let exported_pk = export_public_key(&pk)?;
+        let exported_sig = export_signature(&sig)?;
 
         public_keys.push(PublicKey {
             root: exported_pk.root,
             parameter: exported_pk.parameter,
+            acceptable_roots: Vec::new(),
+            ed25519_key: None,
         });
 
-        signatures_vec.push(Signature {
-            leaf_index: epoch,
-            randomness: exported_sig.randomness,
-            wots_chain_ends: exported_sig.chain_hashes,
-            auth_path: exported_sig.auth_path,
-        });
+        signatures_vec.push(exported_sig.into_xmss_signature(epoch));
     }
 
     let statement = Statement {
@@ -75,25 +265,31 @@ pub fn generate_batch_input(signatures: usize, out_path: &str) -> Result<(), Box
         ep: epoch as u64,
         m: digest.to_vec(),
         public_keys,
+        range: None,
+        allow_extra_public_keys: false,
+        epoch_window: None,
+        randomness_commitment: None,
+        hybrid_ed25519: false,
     };
     let witness = Witness {
         signatures: signatures_vec,
     };
-    let batch = VerificationBatch {
+    let mut batch = VerificationBatch {
         params,
         statement,
         witness,
     };
-
-    // Serialize to OpenVM words -> bytes -> 0x-prefixed hex (with 0x01 prefix marker)
-    let words: Vec<u32> = openvm::serde::to_vec(&batch)?;
-    let mut bytes = Vec::with_capacity(words.len() * 4);
-    for w in words {
-        bytes.extend_from_slice(&w.to_le_bytes());
+    if options.validate {
+        check_batch_verifies(&batch)?;
+    }
+    if let Some(failure) = options.inject_failure {
+        if signatures == 0 {
+            return Err(format!("cannot inject {} into an empty batch", failure.name()).into());
+        }
+        failure.apply(&mut batch);
     }
-    let hex = to_hex(&bytes);
-    let wrapped = format!("0x01{}", hex);
-    let json = format!("{{\n  \"input\": [\"{}\"]\n}}\n", wrapped);
+
+    let json = batch_to_input_json(&batch)?;
 
     if let Some(parent) = Path::new(out_path).parent() {
         if !parent.as_os_str().is_empty() {
@@ -104,6 +300,296 @@ pub fn generate_batch_input(signatures: usize, out_path: &str) -> Result<(), Box
     Ok(())
 }
 
-fn export_err(err: HashsigExportError) -> Box<dyn Error> {
-    Box::new(err)
+/// Fail unless the host reference verifier reports `(true, k)` for `batch`.
+fn check_batch_verifies(batch: &VerificationBatch) -> Result<(), Box<dyn Error>> {
+    let k = batch.statement.k;
+    match reference::verify_batch(batch) {
+        (true, count) if count == k => Ok(()),
+        (all_valid, count) => Err(format!(
+            "generated batch fails reference verification: got (all_valid={all_valid}, \
+             count={count}), expected (true, {k})"
+        )
+        .into()),
+    }
+}
+
+/// Split `batch` into chunks of at most `max_per_chunk` signatures. Every chunk keeps the full
+/// statement and sets `range` to its own slice, so the chunk proofs chain back to the same
+/// signer set; a randomness commitment is recomputed over each chunk's own signatures. Returns
+/// no chunks when `max_per_chunk` is zero or the batch has a malformed range.
+pub fn split_batch(batch: &VerificationBatch, max_per_chunk: usize) -> Vec<VerificationBatch> {
+    let (start, end) = batch.statement.range.unwrap_or((0, batch.statement.k));
+    if max_per_chunk == 0 || start > end {
+        return Vec::new();
+    }
+    batch
+        .witness
+        .signatures
+        .chunks(max_per_chunk)
+        .zip((start..end).step_by(max_per_chunk))
+        .map(|(signatures, chunk_start)| {
+            let mut statement = batch.statement.clone();
+            statement.range = Some((chunk_start, chunk_start + signatures.len() as u32));
+            if statement.randomness_commitment.is_some() {
+                statement.randomness_commitment =
+                    Some(reference::randomness_commitment(signatures));
+            }
+            VerificationBatch {
+                params: batch.params.clone(),
+                statement,
+                witness: Witness {
+                    signatures: signatures.to_vec(),
+                },
+            }
+        })
+        .collect()
+}
+
+/// Print a warning if `batch` (read from `source`) repeats a signature. Proving still goes
+/// ahead; the warning only flags a likely mistake in the input.
+pub fn warn_on_identical_signatures(batch: &VerificationBatch, source: &Path) {
+    if let Some(duplicates) = batch.witness.duplicate_signatures() {
+        eprintln!(
+            "warning: {} of {} signatures in {} duplicate an earlier signature",
+            duplicates,
+            batch.witness.signatures.len(),
+            source.display()
+        );
+    }
+}
+
+/// Render `batch` in the `cargo openvm --input` JSON format.
+pub fn batch_to_input_json(batch: &VerificationBatch) -> Result<String, Box<dyn Error>> {
+    // Serialize to OpenVM words -> bytes -> 0x-prefixed hex (with the INPUT_MARKER byte first)
+    let words: Vec<u32> = openvm::serde::to_vec(batch)?;
+    // Decode the words back so an encoding mismatch surfaces here rather than inside the guest.
+    if try_batch_from_words(&words)? != *batch {
+        return Err("serialized batch does not decode back to the same value".into());
+    }
+    let hex = to_hex(&words_to_le_bytes(&words));
+    let wrapped = format!("0x{:02x}{}", INPUT_MARKER, hex);
+    Ok(format!("{{\n  \"input\": [\"{}\"]\n}}\n", wrapped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::decode::batch_from_input_json;
+    use xmss_lib::reference::BatchStructureError;
+    use xmss_types::Signature;
+
+    #[test]
+    fn word_encoding_probe_is_little_endian() {
+        let words: Vec<u32> = openvm::serde::to_vec(&WORD_ENCODING_PROBE).unwrap();
+        assert_eq!(words, vec![0x0403_0201, 0x0807_0605, 0x0c0b_0a09]);
+        assert_eq!(words_to_le_bytes(&words), WORD_ENCODING_PROBE_BYTES);
+        check_word_encoding().expect("probe encodes as little-endian words");
+    }
+
+    #[test]
+    fn skipping_smoke_test_produces_same_input() {
+        let dir = std::env::temp_dir();
+        let checked = dir.join(format!("xmss-input-checked-{}.json", std::process::id()));
+        let skipped = dir.join(format!("xmss-input-skipped-{}.json", std::process::id()));
+
+        generate_batch_input(1, checked.to_str().unwrap()).unwrap();
+        let options = BatchInputOptions {
+            skip_smoke_test: true,
+            ..BatchInputOptions::default()
+        };
+        generate_batch_input_with(1, skipped.to_str().unwrap(), &options).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&checked).unwrap(),
+            fs::read_to_string(&skipped).unwrap()
+        );
+        let _ = fs::remove_file(checked);
+        let _ = fs::remove_file(skipped);
+    }
+
+    #[test]
+    fn estimated_words_match_serialized_batch() {
+        let node = vec![0u8; PoseidonConfig::DEFAULT.node_bytes()];
+        let n = 3;
+        let batch = VerificationBatch {
+            params: TslParams {
+                w: 2,
+                v: WINTERNITZ_W1_NUM_CHAINS as u16,
+                d0: 0,
+                security_bits: 128,
+                tree_height: WINTERNITZ_TREE_HEIGHT as u16,
+            },
+            statement: Statement {
+                k: n as u32,
+                ep: 0,
+                m: vec![0u8; 32],
+                public_keys: vec![
+                    PublicKey {
+                        root: node.clone(),
+                        parameter: vec![0u8; PoseidonConfig::DEFAULT.parameter_bytes()],
+                        acceptable_roots: Vec::new(),
+                        ed25519_key: None,
+                    };
+                    n
+                ],
+                range: None,
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+            },
+            witness: Witness {
+                signatures: vec![
+                    Signature {
+                        leaf_index: 0,
+                        randomness: vec![0u8; PoseidonConfig::DEFAULT.randomness_bytes()],
+                        wots_chain_ends: vec![node.clone(); WINTERNITZ_W1_NUM_CHAINS],
+                        auth_path: vec![node; WINTERNITZ_TREE_HEIGHT],
+                        auth_path_mask: None,
+                        ed25519_signature: None,
+                    };
+                    n
+                ],
+            },
+        };
+        let words: Vec<u32> = openvm::serde::to_vec(&batch).unwrap();
+        assert_eq!(estimated_input_words(n), words.len());
+    }
+
+    #[test]
+    fn validation_rejects_a_corrupted_batch() {
+        let out =
+            std::env::temp_dir().join(format!("xmss-input-validated-{}.json", std::process::id()));
+        let options = BatchInputOptions {
+            validate: true,
+            ..BatchInputOptions::default()
+        };
+        generate_batch_input_with(2, out.to_str().unwrap(), &options).unwrap();
+        let mut batch = batch_from_input_json(&fs::read_to_string(&out).unwrap()).unwrap();
+        let _ = fs::remove_file(out);
+        check_batch_verifies(&batch).unwrap();
+
+        batch.witness.signatures[1].wots_chain_ends[0][0] ^= 1;
+        let err = check_batch_verifies(&batch).unwrap_err();
+        assert!(err.to_string().contains("expected (true, 2)"), "{err}");
+    }
+
+    #[test]
+    fn generates_inputs_at_a_chosen_epoch() {
+        let out =
+            std::env::temp_dir().join(format!("xmss-input-epoch-{}.json", std::process::id()));
+        let options = BatchInputOptions {
+            validate: true,
+            activation_epoch: 2,
+            signing_epoch: 5,
+            ..BatchInputOptions::default()
+        };
+        generate_batch_input_with(1, out.to_str().unwrap(), &options).unwrap();
+        let batch = batch_from_input_json(&fs::read_to_string(&out).unwrap()).unwrap();
+        let _ = fs::remove_file(&out);
+        assert_eq!(batch.statement.ep, 5);
+        assert_eq!(batch.witness.signatures[0].leaf_index, 5);
+
+        let before_activation = BatchInputOptions {
+            activation_epoch: 6,
+            signing_epoch: 5,
+            ..BatchInputOptions::default()
+        };
+        assert!(generate_batch_input_with(1, out.to_str().unwrap(), &before_activation).is_err());
+        assert!(!out.exists());
+    }
+
+    #[test]
+    fn every_injected_failure_is_rejected() {
+        let id = std::process::id();
+        let dir = std::env::temp_dir().join(format!("xmss-input-inject-keys-{id}"));
+        let out = std::env::temp_dir().join(format!("xmss-input-inject-{id}.json"));
+        for failure in InjectedFailure::ALL {
+            assert_eq!(failure.name().parse(), Ok(failure));
+            let options = BatchInputOptions {
+                validate: true,
+                key_cache_dir: Some(dir.clone()),
+                inject_failure: Some(failure),
+                ..BatchInputOptions::default()
+            };
+            generate_batch_input_with(1, out.to_str().unwrap(), &options).unwrap();
+            let batch = batch_from_input_json(&fs::read_to_string(&out).unwrap()).unwrap();
+            assert!(!reference::verify_batch(&batch).0, "{failure:?}");
+
+            let reason = reference::validate_batch_structure(&batch);
+            match failure {
+                InjectedFailure::BadChainCount => assert!(matches!(
+                    reason,
+                    Err(BatchStructureError::FieldLength {
+                        what: "chain count",
+                        ..
+                    })
+                )),
+                InjectedFailure::BadAuthLen => assert!(matches!(
+                    reason,
+                    Err(BatchStructureError::FieldLength {
+                        what: "auth path height",
+                        ..
+                    })
+                )),
+                // Well-formed; only the recomputed root differs from the key's.
+                InjectedFailure::WrongRoot => assert_eq!(reason, Ok(())),
+                InjectedFailure::BadIndex => {
+                    assert!(matches!(reason, Err(BatchStructureError::LeafIndex { .. })))
+                }
+                InjectedFailure::BadParams => assert!(matches!(
+                    reason,
+                    Err(BatchStructureError::UnsupportedParams { .. })
+                )),
+            }
+        }
+        let _ = fs::remove_file(&out);
+        let _ = fs::remove_dir_all(&dir);
+        assert!("bad-everything".parse::<InjectedFailure>().is_err());
+
+        let empty = BatchInputOptions {
+            inject_failure: Some(InjectedFailure::WrongRoot),
+            ..BatchInputOptions::default()
+        };
+        assert!(generate_batch_input_with(0, out.to_str().unwrap(), &empty).is_err());
+    }
+
+    #[test]
+    fn cached_keys_give_the_same_public_keys_across_runs() {
+        let id = std::process::id();
+        let dir = std::env::temp_dir().join(format!("xmss-input-keys-{id}"));
+        let out = std::env::temp_dir().join(format!("xmss-input-cached-{id}.json"));
+        let options = BatchInputOptions {
+            validate: true,
+            key_cache_dir: Some(dir.clone()),
+            ..BatchInputOptions::default()
+        };
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            generate_batch_input_with(2, out.to_str().unwrap(), &options).unwrap();
+            runs.push(batch_from_input_json(&fs::read_to_string(&out).unwrap()).unwrap());
+        }
+        let cached = fs::read_dir(&dir).unwrap().count();
+        let _ = fs::remove_file(&out);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(cached, 2);
+        assert_eq!(runs[0].statement.public_keys, runs[1].statement.public_keys);
+        assert_ne!(
+            runs[0].statement.public_keys[0],
+            runs[0].statement.public_keys[1]
+        );
+    }
+
+    #[test]
+    fn oversized_batch_fails_precheck() {
+        let out =
+            std::env::temp_dir().join(format!("xmss-input-oversized-{}.json", std::process::id()));
+        let options = BatchInputOptions {
+            max_input_words: Some(estimated_input_words(2) - 1),
+            ..BatchInputOptions::default()
+        };
+        let err = generate_batch_input_with(2, out.to_str().unwrap(), &options).unwrap_err();
+        assert!(err.to_string().contains("exceeding the"), "{err}");
+        assert!(!out.exists());
+    }
 }
@@ -0,0 +1,108 @@
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use rand::SeedableRng;
+use xmss_lib::{SIGWinternitzLifetime18W1, SignatureScheme};
+
+pub type PublicKey = <SIGWinternitzLifetime18W1 as SignatureScheme>::PublicKey;
+pub type SecretKey = <SIGWinternitzLifetime18W1 as SignatureScheme>::SecretKey;
+
+/// Name of the scheme the cache holds keys for, so a different scheme or lifetime never reads
+/// these files.
+const SCHEME_NAME: &str = "SIGWinternitzLifetime18W1";
+
+/// Key generation parameters that, together with the seed, determine a key pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyParams {
+    pub activation_epoch: usize,
+    pub num_active_epochs: usize,
+}
+
+/// Cache file for a key pair. Scheme, seed, and parameters are part of the name, so changing
+/// any of them misses the cache instead of reusing a stale key.
+pub fn key_cache_path(dir: &Path, seed: u64, params: KeyParams) -> PathBuf {
+    dir.join(format!(
+        "keys_{SCHEME_NAME}_{seed:016x}_{}_{}.bin",
+        params.activation_epoch, params.num_active_epochs
+    ))
+}
+
+/// Generate the key pair for `seed` and `params`, or read it back from `dir` when an earlier
+/// call already did. Key generation is deterministic in the seed, so both paths yield the same
+/// keys; an unreadable cache file is regenerated and overwritten. The file holds a secret key,
+/// so it is created readable by the owner only.
+pub fn cached_keygen(
+    dir: &Path,
+    seed: u64,
+    params: KeyParams,
+) -> Result<(PublicKey, SecretKey), Box<dyn Error>> {
+    let path = key_cache_path(dir, seed, params);
+    if let Ok(bytes) = fs::read(&path) {
+        if let Ok(keys) = bincode::deserialize(&bytes) {
+            return Ok(keys);
+        }
+    }
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let keys = SIGWinternitzLifetime18W1::key_gen(
+        &mut rng,
+        params.activation_epoch,
+        params.num_active_epochs,
+    );
+    fs::create_dir_all(dir)?;
+    write_owner_only(&path, &bincode::serialize(&keys)?)?;
+    Ok(keys)
+}
+
+fn write_owner_only(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_call_reuses_the_cached_keys() {
+        let dir = std::env::temp_dir().join(format!("xmss-key-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let params = KeyParams {
+            activation_epoch: 0,
+            num_active_epochs: 1,
+        };
+        let path = key_cache_path(&dir, 7, params);
+        assert!(!path.exists());
+        assert!(path.to_str().unwrap().contains(SCHEME_NAME));
+
+        let first = cached_keygen(&dir, 7, params).unwrap();
+        assert!(path.exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Swap in the keys of another seed: a cache hit returns them instead of regenerating.
+        let other_seed = cached_keygen(&dir, 8, params).unwrap();
+        let other_bytes = bincode::serialize(&other_seed).unwrap();
+        assert_ne!(bincode::serialize(&first).unwrap(), other_bytes);
+        fs::write(&path, &other_bytes).unwrap();
+        let cached = cached_keygen(&dir, 7, params).unwrap();
+        assert_eq!(bincode::serialize(&cached).unwrap(), other_bytes);
+
+        let other = KeyParams {
+            num_active_epochs: 2,
+            ..params
+        };
+        assert_ne!(key_cache_path(&dir, 7, other), path);
+        assert_ne!(key_cache_path(&dir, 8, params), path);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -1,7 +1,10 @@
 use std::error::Error;
 use std::path::PathBuf;
 
+pub mod backend;
+pub mod decode;
 pub mod input;
+pub mod keys;
 pub mod mem;
 pub mod openvm;
 
@@ -1,16 +1,20 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::error::Error;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::OnceLock;
+use std::sync::Mutex;
 
 type DynError = Box<dyn Error>;
 
-static KEYGEN_STATUS: OnceLock<Result<(), String>> = OnceLock::new();
+/// Keygen outcome per guest directory, so each directory is set up at most once per process.
+static KEYGEN_STATUS: Mutex<BTreeMap<PathBuf, Result<(), String>>> = Mutex::new(BTreeMap::new());
 
-pub fn run_in_guest<const N: usize>(args: [&str; N]) -> Result<(), DynError> {
-    ensure_guest_keygen()?;
+/// Run `cargo openvm <args>` in the guest crate at `guest_dir`, generating its keys first.
+pub fn run_in_guest<const N: usize>(guest_dir: &Path, args: [&str; N]) -> Result<(), DynError> {
+    ensure_guest_keygen(guest_dir)?;
 
-    let (mut cmd, mut rendered_args) = cargo_openvm_base_cmd();
+    let (mut cmd, mut rendered_args) = cargo_openvm_base_cmd(guest_dir);
 
     for a in args.into_iter() {
         cmd.arg(a);
@@ -19,16 +23,17 @@ pub fn run_in_guest<const N: usize>(args: [&str; N]) -> Result<(), DynError> {
     let status = cmd.status()?;
     if !status.success() {
         return Err(format!(
-            "Command failed: cargo {} (in guest/). Ensure cargo-openvm is installed and keys are generated.",
-            rendered_args.join(" ")
+            "Command failed: cargo {} (in {}). Ensure cargo-openvm is installed and keys are generated.",
+            rendered_args.join(" "),
+            guest_dir.display()
         ).into());
     }
     Ok(())
 }
 
-fn cargo_openvm_base_cmd() -> (Command, Vec<String>) {
+fn cargo_openvm_base_cmd(guest_dir: &Path) -> (Command, Vec<String>) {
     let mut cmd = Command::new("cargo");
-    cmd.current_dir("guest");
+    cmd.current_dir(guest_dir);
     cmd.arg("openvm");
 
     let mut rendered_args = vec![String::from("openvm")];
@@ -43,22 +48,27 @@ fn cargo_openvm_base_cmd() -> (Command, Vec<String>) {
     (cmd, rendered_args)
 }
 
-fn ensure_guest_keygen() -> Result<(), DynError> {
-    match KEYGEN_STATUS.get_or_init(|| run_guest_keygen().map_err(|e| e.to_string())) {
+fn ensure_guest_keygen(guest_dir: &Path) -> Result<(), DynError> {
+    let mut status = KEYGEN_STATUS.lock().unwrap();
+    let result = status
+        .entry(guest_dir.to_path_buf())
+        .or_insert_with(|| run_guest_keygen(guest_dir).map_err(|e| e.to_string()));
+    match result {
         Ok(_) => Ok(()),
         Err(msg) => Err(msg.clone().into()),
     }
 }
 
-fn run_guest_keygen() -> Result<(), DynError> {
-    let (mut cmd, mut rendered_args) = cargo_openvm_base_cmd();
+fn run_guest_keygen(guest_dir: &Path) -> Result<(), DynError> {
+    let (mut cmd, mut rendered_args) = cargo_openvm_base_cmd(guest_dir);
     cmd.arg("keygen");
     rendered_args.push(String::from("keygen"));
     let status = cmd.status()?;
     if !status.success() {
         return Err(format!(
-            "Command failed: cargo {} (in guest/) while running keygen. Install cargo-openvm?",
-            rendered_args.join(" ")
+            "Command failed: cargo {} (in {}) while running keygen. Install cargo-openvm?",
+            rendered_args.join(" "),
+            guest_dir.display()
         )
         .into());
     }
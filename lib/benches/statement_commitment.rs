@@ -0,0 +1,50 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use xmss_lib::hashsig_export::{
+    POSEIDON_FE_BYTES, POSEIDON_HASH_LEN_FE, POSEIDON_PARAMETER_LEN_FE,
+};
+use xmss_lib::reference::statement_commitment;
+use xmss_types::{PublicKey, Statement};
+
+const KEY_COUNTS: [usize; 3] = [10, 100, 1_000];
+
+fn statement_with_keys(count: usize) -> Statement {
+    let public_keys = (0..count)
+        .map(|i| PublicKey {
+            root: vec![i as u8; POSEIDON_HASH_LEN_FE * POSEIDON_FE_BYTES],
+            parameter: vec![(i >> 8) as u8; POSEIDON_PARAMETER_LEN_FE * POSEIDON_FE_BYTES],
+            acceptable_roots: Vec::new(),
+            ed25519_key: None,
+        })
+        .collect();
+    Statement {
+        k: count as u32,
+        ep: 0,
+        m: xmss_lib::hash_message_to_digest(b"statement-commitment-bench").to_vec(),
+        public_keys,
+        range: None,
+        allow_extra_public_keys: false,
+        epoch_window: None,
+        randomness_commitment: None,
+        hybrid_ed25519: false,
+    }
+}
+
+fn bench_statement_commitment(c: &mut Criterion) {
+    let mut group = c.benchmark_group("statement_commitment");
+    for count in KEY_COUNTS {
+        let statement = statement_with_keys(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &statement, |b, stmt| {
+            b.iter(|| {
+                let commitment = statement_commitment(black_box(stmt));
+                assert_ne!(commitment, [0u8; 32]);
+                commitment
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_statement_commitment);
+criterion_main!(benches);
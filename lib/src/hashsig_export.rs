@@ -1,25 +1,42 @@
+use std::borrow::Cow;
 use std::fmt;
 
 use bincode::Options;
-use p3_field::PrimeField64;
+use hashsig::symmetric::tweak_hash::{poseidon::PoseidonTweakHash, TweakableHash};
+use p3_field::{PrimeCharacteristicRing, PrimeField64};
 use p3_koala_bear::KoalaBear;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+pub use xmss_types::PoseidonConfig;
 
 use crate::SIGWinternitzLifetime18W1;
 
 /// Number of field elements used to encode a Poseidon hash domain element.
-pub const POSEIDON_HASH_LEN_FE: usize = 7;
+pub const POSEIDON_HASH_LEN_FE: usize = PoseidonConfig::DEFAULT.hash_len_fe;
 /// Number of field elements used for the Poseidon public parameter.
-pub const POSEIDON_PARAMETER_LEN_FE: usize = 5;
+pub const POSEIDON_PARAMETER_LEN_FE: usize = PoseidonConfig::DEFAULT.parameter_len_fe;
 /// Number of field elements used for the Winternitz randomness (rho).
-pub const POSEIDON_RANDOMNESS_LEN_FE: usize = 5;
+pub const POSEIDON_RANDOMNESS_LEN_FE: usize = PoseidonConfig::DEFAULT.randomness_len_fe;
 /// Number of KoalaBear bytes per field element.
-pub const POSEIDON_FE_BYTES: usize = core::mem::size_of::<KoalaBear>();
+pub const POSEIDON_FE_BYTES: usize = PoseidonConfig::FE_BYTES;
+const _: () = assert!(POSEIDON_FE_BYTES == core::mem::size_of::<KoalaBear>());
 /// Number of Winternitz chains for the w=1 instantiation.
 pub const WINTERNITZ_W1_NUM_CHAINS: usize = 163;
 /// Merkle tree height for lifetime 2^18.
 pub const WINTERNITZ_TREE_HEIGHT: usize = 18;
 
+const POSEIDON_TWEAK_LEN_FE: usize = 2;
+const POSEIDON_CAPACITY_LEN: usize = 9;
+
+/// Tweakable hash of [`SIGWinternitzLifetime18W1`], used to rebuild omitted auth-path nodes.
+type TweakHash = PoseidonTweakHash<
+    POSEIDON_PARAMETER_LEN_FE,
+    POSEIDON_HASH_LEN_FE,
+    POSEIDON_TWEAK_LEN_FE,
+    POSEIDON_CAPACITY_LEN,
+    WINTERNITZ_W1_NUM_CHAINS,
+>;
+
 /// Host-facing representation of a Poseidon XMSS public key.
 pub struct ExportedPublicKey {
     pub root: Vec<u8>,
@@ -33,10 +50,34 @@ pub struct ExportedSignature {
     pub auth_path: Vec<Vec<u8>>,
 }
 
+impl ExportedSignature {
+    /// The batch-format signature for the leaf this signature was made with, with every auth
+    /// path node present and no cosignature.
+    pub fn into_xmss_signature(self, leaf_index: u32) -> xmss_types::Signature {
+        xmss_types::Signature {
+            leaf_index,
+            randomness: self.randomness,
+            wots_chain_ends: self.chain_hashes,
+            auth_path: self.auth_path,
+            auth_path_mask: None,
+            ed25519_signature: None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum HashsigExportError {
     Serialization(String),
-    UnexpectedChainCount { expected: usize, actual: usize },
+    UnexpectedChainCount {
+        expected: usize,
+        actual: usize,
+    },
+    UnexpectedLength {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    CompressedAuthPath,
 }
 
 impl fmt::Display for HashsigExportError {
@@ -48,6 +89,19 @@ impl fmt::Display for HashsigExportError {
             HashsigExportError::UnexpectedChainCount { expected, actual } => {
                 write!(f, "unexpected chain count {actual} (expected {expected})")
             }
+            HashsigExportError::UnexpectedLength {
+                field,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "unexpected {field} length {actual} (expected {expected})"
+                )
+            }
+            HashsigExportError::CompressedAuthPath => {
+                write!(f, "compressed auth paths cannot be imported into hash-sig")
+            }
         }
     }
 }
@@ -60,19 +114,19 @@ impl From<bincode::Error> for HashsigExportError {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 struct RawSignature {
     path: RawPath,
     rho: [KoalaBear; POSEIDON_RANDOMNESS_LEN_FE],
     hashes: Vec<[KoalaBear; POSEIDON_HASH_LEN_FE]>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 struct RawPath {
     co_path: Vec<[KoalaBear; POSEIDON_HASH_LEN_FE]>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 struct RawPublicKey {
     root: [KoalaBear; POSEIDON_HASH_LEN_FE],
     parameter: [KoalaBear; POSEIDON_PARAMETER_LEN_FE],
@@ -106,6 +160,34 @@ fn domains_to_bytes(domains: &[[KoalaBear; POSEIDON_HASH_LEN_FE]]) -> Vec<Vec<u8
         .collect()
 }
 
+fn bytes_to_field_array<const N: usize>(
+    bytes: &[u8],
+    field: &'static str,
+) -> Result<[KoalaBear; N], HashsigExportError> {
+    if bytes.len() != N * POSEIDON_FE_BYTES {
+        return Err(HashsigExportError::UnexpectedLength {
+            field,
+            expected: N * POSEIDON_FE_BYTES,
+            actual: bytes.len(),
+        });
+    }
+    let mut out = [KoalaBear::ZERO; N];
+    for (slot, chunk) in out.iter_mut().zip(bytes.chunks_exact(POSEIDON_FE_BYTES)) {
+        *slot = KoalaBear::from_u32(u32::from_le_bytes(chunk.try_into().unwrap()));
+    }
+    Ok(out)
+}
+
+fn bytes_to_domains(
+    nodes: &[Vec<u8>],
+    field: &'static str,
+) -> Result<Vec<[KoalaBear; POSEIDON_HASH_LEN_FE]>, HashsigExportError> {
+    nodes
+        .iter()
+        .map(|node| bytes_to_field_array(node, field))
+        .collect()
+}
+
 /// Convert a hash-sig Poseidon public key into raw byte vectors.
 pub fn export_public_key(
     pk: &<SIGWinternitzLifetime18W1 as hashsig::signature::SignatureScheme>::PublicKey,
@@ -135,6 +217,104 @@ pub fn export_signature(
     })
 }
 
+/// Rebuild a hash-sig Poseidon public key from its exported byte form.
+pub fn import_public_key(
+    pk: &xmss_types::PublicKey,
+) -> Result<
+    <SIGWinternitzLifetime18W1 as hashsig::signature::SignatureScheme>::PublicKey,
+    HashsigExportError,
+> {
+    let raw = RawPublicKey {
+        root: bytes_to_field_array(&pk.root, "root")?,
+        parameter: bytes_to_field_array(&pk.parameter, "parameter")?,
+    };
+    deserialize_via_bincode(&raw)
+}
+
+/// Expand a compressed auth path to every sibling under the key `parameter`, substituting the
+/// default node for each level the mask omits, as the guest does. The default node of level 0
+/// is all zeros; each higher level hashes two copies of the level below with the tree tweak for
+/// `(level, 0)`. Signatures without a mask are returned unchanged.
+pub fn expand_auth_path<'a>(
+    sig: &'a xmss_types::Signature,
+    parameter: &[u8],
+) -> Result<Cow<'a, xmss_types::Signature>, HashsigExportError> {
+    let Some(mask) = &sig.auth_path_mask else {
+        return Ok(Cow::Borrowed(sig));
+    };
+    let present = mask.iter().filter(|&&present| present).count();
+    if present != sig.auth_path.len() {
+        return Err(HashsigExportError::UnexpectedLength {
+            field: "auth_path",
+            expected: present,
+            actual: sig.auth_path.len(),
+        });
+    }
+    let parameter: <TweakHash as TweakableHash>::Parameter = deserialize_via_bincode(
+        &bytes_to_field_array::<POSEIDON_PARAMETER_LEN_FE>(parameter, "parameter")?,
+    )?;
+
+    let mut present_nodes = sig.auth_path.iter();
+    let mut auth_path = Vec::with_capacity(mask.len());
+    // Default nodes are built bottom-up once, only as high as the omitted levels require.
+    let mut default: <TweakHash as TweakableHash>::Domain =
+        deserialize_via_bincode(&[KoalaBear::ZERO; POSEIDON_HASH_LEN_FE])?;
+    let mut default_level = 0;
+    for (level, &present) in mask.iter().enumerate() {
+        if present {
+            auth_path.extend(present_nodes.next().cloned());
+        } else {
+            while default_level < level {
+                default_level += 1;
+                let tweak = TweakHash::tree_tweak(default_level as u8, 0);
+                default = TweakHash::apply(&parameter, &tweak, &[default, default]);
+            }
+            let node: [KoalaBear; POSEIDON_HASH_LEN_FE] = deserialize_via_bincode(&default)?;
+            auth_path.push(field_array_to_bytes(&node));
+        }
+    }
+    Ok(Cow::Owned(xmss_types::Signature {
+        auth_path,
+        auth_path_mask: None,
+        ..sig.clone()
+    }))
+}
+
+/// Rebuild a hash-sig Poseidon signature from the byte vectors stored in xmss-types.
+/// Compressed auth paths are rejected, since hash-sig expects every sibling; expand them with
+/// [`expand_auth_path`] first.
+pub fn import_signature(
+    sig: &xmss_types::Signature,
+) -> Result<
+    <SIGWinternitzLifetime18W1 as hashsig::signature::SignatureScheme>::Signature,
+    HashsigExportError,
+> {
+    if sig.auth_path_mask.is_some() {
+        return Err(HashsigExportError::CompressedAuthPath);
+    }
+    if sig.wots_chain_ends.len() != WINTERNITZ_W1_NUM_CHAINS {
+        return Err(HashsigExportError::UnexpectedChainCount {
+            expected: WINTERNITZ_W1_NUM_CHAINS,
+            actual: sig.wots_chain_ends.len(),
+        });
+    }
+    if sig.auth_path.len() != WINTERNITZ_TREE_HEIGHT {
+        return Err(HashsigExportError::UnexpectedLength {
+            field: "auth_path",
+            expected: WINTERNITZ_TREE_HEIGHT,
+            actual: sig.auth_path.len(),
+        });
+    }
+    let raw = RawSignature {
+        path: RawPath {
+            co_path: bytes_to_domains(&sig.auth_path, "auth_path node")?,
+        },
+        rho: bytes_to_field_array(&sig.randomness, "randomness")?,
+        hashes: bytes_to_domains(&sig.wots_chain_ends, "chain end")?,
+    };
+    deserialize_via_bincode(&raw)
+}
+
 #[cfg(test)]
 mod tests {
     use rand::SeedableRng;
@@ -166,6 +346,16 @@ mod tests {
             .iter()
             .all(|node| node.len() == POSEIDON_HASH_LEN_FE * POSEIDON_FE_BYTES));
 
+        let converted = exported_sig.into_xmss_signature(0);
+        assert_eq!(converted.leaf_index, 0);
+        assert_eq!(converted.wots_chain_ends.len(), WINTERNITZ_W1_NUM_CHAINS);
+        assert_eq!(converted.auth_path.len(), WINTERNITZ_TREE_HEIGHT);
+        assert_eq!(
+            converted.randomness.len(),
+            POSEIDON_RANDOMNESS_LEN_FE * POSEIDON_FE_BYTES
+        );
+        assert!(converted.auth_path_mask.is_none());
+
         let exported_pk = export_public_key(&pk).expect("public key exports");
         assert_eq!(
             exported_pk.root.len(),
@@ -175,5 +365,71 @@ mod tests {
             exported_pk.parameter.len(),
             POSEIDON_PARAMETER_LEN_FE * POSEIDON_FE_BYTES
         );
+
+        let config = PoseidonConfig::default();
+        let payload = exported_sig.randomness.len()
+            + exported_sig
+                .chain_hashes
+                .iter()
+                .map(Vec::len)
+                .sum::<usize>()
+            + exported_sig.auth_path.iter().map(Vec::len).sum::<usize>();
+        assert_eq!(
+            payload,
+            config.signature_bytes(WINTERNITZ_W1_NUM_CHAINS, WINTERNITZ_TREE_HEIGHT)
+        );
+        assert_eq!(exported_pk.root.len(), config.node_bytes());
+    }
+
+    #[test]
+    fn expanded_auth_path_fills_omitted_levels_with_default_nodes() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xC0FFEE);
+        let (pk, sk) = SIGWinternitzLifetime18W1::key_gen(&mut rng, 0, 1);
+        let digest = hash_message_to_digest(b"compressed-path");
+        let sig = SIGWinternitzLifetime18W1::sign(&mut rng, &sk, 0, &digest).unwrap();
+        let parameter = export_public_key(&pk).unwrap().parameter;
+        let full = export_signature(&sig).unwrap().into_xmss_signature(0);
+        assert!(matches!(
+            expand_auth_path(&full, &parameter).unwrap(),
+            Cow::Borrowed(_)
+        ));
+
+        let omitted = [0usize, 5, 17];
+        let mut compressed = full.clone();
+        compressed.auth_path_mask = Some(
+            (0..WINTERNITZ_TREE_HEIGHT)
+                .map(|level| !omitted.contains(&level))
+                .collect(),
+        );
+        compressed.auth_path = full
+            .auth_path
+            .iter()
+            .enumerate()
+            .filter(|(level, _)| !omitted.contains(level))
+            .map(|(_, node)| node.clone())
+            .collect();
+
+        let expanded = expand_auth_path(&compressed, &parameter).unwrap();
+        assert!(expanded.auth_path_mask.is_none());
+        assert_eq!(expanded.auth_path.len(), WINTERNITZ_TREE_HEIGHT);
+        for (level, node) in expanded.auth_path.iter().enumerate() {
+            if !omitted.contains(&level) {
+                assert_eq!(node, &full.auth_path[level]);
+            }
+        }
+        assert_eq!(
+            expanded.auth_path[0],
+            vec![0u8; POSEIDON_HASH_LEN_FE * POSEIDON_FE_BYTES]
+        );
+        assert_ne!(expanded.auth_path[5], expanded.auth_path[17]);
+
+        compressed.auth_path.pop();
+        assert!(matches!(
+            expand_auth_path(&compressed, &parameter),
+            Err(HashsigExportError::UnexpectedLength {
+                field: "auth_path",
+                ..
+            })
+        ));
     }
 }
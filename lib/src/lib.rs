@@ -2,6 +2,9 @@ use std::error::Error;
 use std::fmt::{self, Display};
 
 pub mod hashsig_export;
+pub mod reference;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod zkvm;
 
 pub use zkvm::ZkvmHost;
@@ -17,6 +20,12 @@ pub enum XmssHostError {
         activation_epoch: usize,
         num_active_epochs: usize,
     },
+    MessageTooLong {
+        len: usize,
+        max_len: usize,
+    },
+    Export(String),
+    ThreadPool(String),
 }
 
 impl Display for XmssHostError {
@@ -33,12 +42,27 @@ impl Display for XmssHostError {
                 activation_epoch,
                 activation_epoch + num_active_epochs
             ),
+            XmssHostError::MessageTooLong { len, max_len } => {
+                write!(
+                    f,
+                    "message of {} bytes exceeds the {} byte limit",
+                    len, max_len
+                )
+            }
+            XmssHostError::Export(msg) => write!(f, "hash-sig export failed: {}", msg),
+            XmssHostError::ThreadPool(msg) => write!(f, "failed to build thread pool: {}", msg),
         }
     }
 }
 
 impl Error for XmssHostError {}
 
+impl From<hashsig_export::HashsigExportError> for XmssHostError {
+    fn from(err: hashsig_export::HashsigExportError) -> Self {
+        XmssHostError::Export(err.to_string())
+    }
+}
+
 /// Ensure the requested epoch falls inside the activation range supplied during key generation.
 pub fn validate_epoch_range(
     activation_epoch: usize,
@@ -73,11 +97,59 @@ pub fn hash_message_to_digest(message: &[u8]) -> [u8; 32] {
     hasher.finalize().into()
 }
 
+/// Like [`hash_message_to_digest`], but hashes `len(message)` as a little-endian `u64` before the
+/// message, so a message cannot be confused with a prefix or extension of another one when
+/// messages are concatenated with other context elsewhere. Framed and plain digests of the same
+/// message differ; signers and verifiers must agree on which one is signed.
+pub fn hash_message_to_digest_framed(message: &[u8]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update((message.len() as u64).to_le_bytes());
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// Like [`hash_message_to_digest`], but refuses messages longer than `max_len` bytes before
+/// hashing them, so callers exposed to untrusted input can bound the work done.
+pub fn hash_message_to_digest_bounded(
+    message: &[u8],
+    max_len: usize,
+) -> Result<[u8; 32], XmssHostError> {
+    if message.len() > max_len {
+        return Err(XmssHostError::MessageTooLong {
+            len: message.len(),
+            max_len,
+        });
+    }
+    Ok(hash_message_to_digest(message))
+}
+
+/// Run `op` on a dedicated rayon pool of `threads` workers, so parallel helpers such as
+/// [`reference::verify_parallel`] stay within that many cores. `None` uses the global pool,
+/// which defaults to one thread per core.
+pub fn run_with_threads<R, F>(threads: Option<usize>, op: F) -> Result<R, XmssHostError>
+where
+    R: Send,
+    F: FnOnce() -> R + Send,
+{
+    match threads {
+        None => Ok(op()),
+        Some(n) => rayon::ThreadPoolBuilder::new()
+            .num_threads(n)
+            .build()
+            .map(|pool| pool.install(op))
+            .map_err(|e| XmssHostError::ThreadPool(e.to_string())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        hash_message_to_digest, validate_epoch_range, SIGWinternitzLifetime18W1, XmssHostError,
+        hash_message_to_digest, hash_message_to_digest_bounded, hash_message_to_digest_framed,
+        run_with_threads, validate_epoch_range, SIGWinternitzLifetime18W1, XmssHostError,
     };
+    use crate::hashsig_export::HashsigExportError;
     use hashsig::signature::SignatureScheme;
     use rand::SeedableRng;
 
@@ -112,4 +184,72 @@ mod tests {
             }
         ));
     }
+
+    #[test]
+    fn framed_digest_is_deterministic_and_distinct() {
+        let message = b"framed message";
+        assert_eq!(
+            hash_message_to_digest_framed(message),
+            hash_message_to_digest_framed(message)
+        );
+        assert_ne!(
+            hash_message_to_digest_framed(message),
+            hash_message_to_digest(message)
+        );
+
+        let mut framed = (message.len() as u64).to_le_bytes().to_vec();
+        framed.extend_from_slice(message);
+        assert_eq!(
+            hash_message_to_digest_framed(message),
+            hash_message_to_digest(&framed)
+        );
+    }
+
+    #[test]
+    fn bounded_digest_accepts_message_at_limit() {
+        let message = [7u8; 64];
+        assert_eq!(
+            hash_message_to_digest_bounded(&message, 64),
+            Ok(hash_message_to_digest(&message))
+        );
+    }
+
+    #[test]
+    fn bounded_digest_rejects_message_over_limit() {
+        let err = hash_message_to_digest_bounded(&[7u8; 65], 64).expect_err("over the limit");
+        assert_eq!(
+            err,
+            XmssHostError::MessageTooLong {
+                len: 65,
+                max_len: 64
+            }
+        );
+    }
+
+    #[test]
+    fn export_errors_convert_with_message() {
+        let err: XmssHostError = HashsigExportError::UnexpectedChainCount {
+            expected: 163,
+            actual: 162,
+        }
+        .into();
+        assert_eq!(
+            err,
+            XmssHostError::Export("unexpected chain count 162 (expected 163)".to_string())
+        );
+        assert_eq!(
+            err.to_string(),
+            "hash-sig export failed: unexpected chain count 162 (expected 163)"
+        );
+    }
+
+    #[test]
+    fn configured_thread_count_is_respected() {
+        assert_eq!(run_with_threads(Some(2), rayon::current_num_threads), Ok(2));
+        assert_eq!(run_with_threads(Some(1), rayon::current_num_threads), Ok(1));
+        assert_eq!(
+            run_with_threads(None, rayon::current_num_threads),
+            Ok(rayon::current_num_threads())
+        );
+    }
 }
@@ -0,0 +1,1284 @@
+//! Host-side reference verifier for `xmss-types` batches.
+//!
+//! Applies the same batch-level rules as the guest program, but checks each signature with
+//! hash-sig itself so a batch can be validated before spending time on a proof.
+
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use xmss_types::{
+    DetailedVerificationResult, PublicKey, SigStatus, Signature, Statement, TslParams,
+    VerificationBatch,
+};
+
+use crate::hashsig_export::{
+    expand_auth_path, import_public_key, import_signature, PoseidonConfig, WINTERNITZ_TREE_HEIGHT,
+    WINTERNITZ_W1_NUM_CHAINS,
+};
+use crate::{SIGWinternitzLifetime18W1, SignatureScheme};
+
+const NODE_BYTES: usize = PoseidonConfig::DEFAULT.node_bytes();
+const PARAMETER_BYTES: usize = PoseidonConfig::DEFAULT.parameter_bytes();
+const RANDOMNESS_BYTES: usize = PoseidonConfig::DEFAULT.randomness_bytes();
+const MESSAGE_BYTES: usize = 32;
+
+/// Structural problems detected before any signature is checked. Signature indices are
+/// positions in the witness; public key indices are positions in the statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchStructureError {
+    UnsupportedParams {
+        w: u16,
+        v: u16,
+        tree_height: u16,
+    },
+    InvalidRange {
+        start: u32,
+        end: u32,
+        k: u32,
+    },
+    CountMismatch {
+        what: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    MessageLength {
+        actual: usize,
+    },
+    EpochOutOfRange {
+        ep: u64,
+    },
+    FieldLength {
+        what: &'static str,
+        index: usize,
+        expected: usize,
+        actual: usize,
+    },
+    AuthPathMask {
+        index: usize,
+        present: usize,
+        nodes: usize,
+    },
+    LeafIndex {
+        index: usize,
+        leaf_index: u32,
+        ep: u64,
+    },
+    PublicKeyLengthMismatch {
+        index: usize,
+        root_len: usize,
+        parameter_len: usize,
+        expected_root_len: usize,
+        expected_parameter_len: usize,
+    },
+}
+
+impl fmt::Display for BatchStructureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchStructureError::UnsupportedParams { w, v, tree_height } => write!(
+                f,
+                "unsupported parameters w={w}, v={v}, tree_height={tree_height} \
+                 (expected w=2, v={WINTERNITZ_W1_NUM_CHAINS}, tree_height={WINTERNITZ_TREE_HEIGHT})"
+            ),
+            BatchStructureError::InvalidRange { start, end, k } => {
+                write!(f, "signature range [{start}, {end}) is not within k={k}")
+            }
+            BatchStructureError::CountMismatch {
+                what,
+                expected,
+                actual,
+            } => write!(f, "expected {expected} {what}, found {actual}"),
+            BatchStructureError::MessageLength { actual } => {
+                write!(f, "message is {actual} bytes (expected {MESSAGE_BYTES})")
+            }
+            BatchStructureError::EpochOutOfRange { ep } => {
+                write!(f, "epoch {ep} does not fit the key lifetime")
+            }
+            BatchStructureError::FieldLength {
+                what,
+                index,
+                expected,
+                actual,
+            } => write!(f, "{what} of entry {index} has length {actual} (expected {expected})"),
+            BatchStructureError::AuthPathMask {
+                index,
+                present,
+                nodes,
+            } => write!(
+                f,
+                "signature {index} auth-path mask marks {present} levels present but carries {nodes} nodes"
+            ),
+            BatchStructureError::LeafIndex {
+                index,
+                leaf_index,
+                ep,
+            } => write!(f, "signature {index} uses leaf {leaf_index} for epoch {ep}"),
+            BatchStructureError::PublicKeyLengthMismatch {
+                index,
+                root_len,
+                parameter_len,
+                expected_root_len,
+                expected_parameter_len,
+            } => write!(
+                f,
+                "public key {index} has root/parameter lengths {root_len}/{parameter_len} \
+                 (expected {expected_root_len}/{expected_parameter_len})"
+            ),
+        }
+    }
+}
+
+impl Error for BatchStructureError {}
+
+/// Check every structural invariant the guest relies on, before any hashing: parameter set,
+/// range and counts, message and node sizes, auth-path shape, and leaf indices. Returns the
+/// first violation found.
+pub fn validate_batch_structure(batch: &VerificationBatch) -> Result<(), BatchStructureError> {
+    let params = &batch.params;
+    if !params_match(params) {
+        return Err(BatchStructureError::UnsupportedParams {
+            w: params.w,
+            v: params.v,
+            tree_height: params.tree_height,
+        });
+    }
+
+    let statement = &batch.statement;
+    let Some((start, end)) = signature_range(statement) else {
+        let (start, end) = statement.range.unwrap_or((0, statement.k));
+        return Err(BatchStructureError::InvalidRange {
+            start,
+            end,
+            k: statement.k,
+        });
+    };
+    let Some(public_keys) = bound_public_keys(statement) else {
+        return Err(BatchStructureError::CountMismatch {
+            what: "public keys",
+            expected: statement.k as usize,
+            actual: statement.public_keys.len(),
+        });
+    };
+    let signatures = &batch.witness.signatures;
+    expect_count("signatures", end - start, signatures.len())?;
+
+    if statement.m.len() != MESSAGE_BYTES {
+        return Err(BatchStructureError::MessageLength {
+            actual: statement.m.len(),
+        });
+    }
+    if statement.ep >= 1u64 << WINTERNITZ_TREE_HEIGHT {
+        return Err(BatchStructureError::EpochOutOfRange { ep: statement.ep });
+    }
+
+    for (index, pk) in public_keys.iter().enumerate() {
+        expect_len("root", index, NODE_BYTES, pk.root.len())?;
+        expect_len("parameter", index, PARAMETER_BYTES, pk.parameter.len())?;
+        for root in &pk.acceptable_roots {
+            expect_len("acceptable root", index, NODE_BYTES, root.len())?;
+        }
+    }
+
+    for (index, sig) in signatures.iter().enumerate() {
+        expect_len("randomness", index, RANDOMNESS_BYTES, sig.randomness.len())?;
+        expect_len(
+            "chain count",
+            index,
+            WINTERNITZ_W1_NUM_CHAINS,
+            sig.wots_chain_ends.len(),
+        )?;
+        for chain_end in &sig.wots_chain_ends {
+            expect_len("chain end", index, NODE_BYTES, chain_end.len())?;
+        }
+        expect_len(
+            "auth path height",
+            index,
+            WINTERNITZ_TREE_HEIGHT,
+            auth_path_height(sig),
+        )?;
+        if let Some(mask) = &sig.auth_path_mask {
+            let present = mask.iter().filter(|&&p| p).count();
+            if present != sig.auth_path.len() {
+                return Err(BatchStructureError::AuthPathMask {
+                    index,
+                    present,
+                    nodes: sig.auth_path.len(),
+                });
+            }
+        }
+        for node in &sig.auth_path {
+            expect_len("auth path node", index, NODE_BYTES, node.len())?;
+        }
+        if sig.leaf_index as u64 != statement.ep {
+            return Err(BatchStructureError::LeafIndex {
+                index,
+                leaf_index: sig.leaf_index,
+                ep: statement.ep,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn expect_count(
+    what: &'static str,
+    expected: usize,
+    actual: usize,
+) -> Result<(), BatchStructureError> {
+    if expected != actual {
+        return Err(BatchStructureError::CountMismatch {
+            what,
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+fn expect_len(
+    what: &'static str,
+    index: usize,
+    expected: usize,
+    actual: usize,
+) -> Result<(), BatchStructureError> {
+    if expected != actual {
+        return Err(BatchStructureError::FieldLength {
+            what,
+            index,
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Ensure every public key uses the same root and parameter lengths as the first one.
+pub fn check_public_key_lengths(statement: &Statement) -> Result<(), BatchStructureError> {
+    let public_keys = bound_public_keys(statement).unwrap_or(&statement.public_keys);
+    let Some(first) = public_keys.first() else {
+        return Ok(());
+    };
+    for (index, pk) in public_keys.iter().enumerate().skip(1) {
+        if pk.root.len() != first.root.len() || pk.parameter.len() != first.parameter.len() {
+            return Err(BatchStructureError::PublicKeyLengthMismatch {
+                index,
+                root_len: pk.root.len(),
+                parameter_len: pk.parameter.len(),
+                expected_root_len: first.root.len(),
+                expected_parameter_len: first.parameter.len(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Whether [`verify_batch_detailed`] checks every signature or stops at the first invalid one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyMode {
+    Exhaustive,
+    FailFast,
+}
+
+/// Verify a batch with hash-sig, returning `(all_valid, count)` exactly like the guest.
+/// Malformed batches report `(false, 0)`.
+pub fn verify_batch(batch: &VerificationBatch) -> (bool, u32) {
+    summarize(batch, VerifyMode::Exhaustive)
+}
+
+fn summarize(batch: &VerificationBatch, mode: VerifyMode) -> (bool, u32) {
+    match verify_batch_detailed(batch, mode) {
+        Some(detailed) => {
+            let summary = detailed.summary();
+            (
+                summary.all_signatures_valid,
+                summary.num_signatures_verified as u32,
+            )
+        }
+        None => (false, 0),
+    }
+}
+
+/// Host-only strict [`verify_batch`]: `(false, 0)` when two verified signatures under the same
+/// public-key root reuse a `leaf_index`. No statement flag selects this in the guest, so a proof
+/// does not attest it; run it on the witness before proving when reuse must be ruled out.
+pub fn verify_batch_strict(batch: &VerificationBatch) -> (bool, u32) {
+    if reuses_leaf_index(batch) {
+        return (false, 0);
+    }
+    verify_batch(batch)
+}
+
+/// Host-only hybrid [`verify_batch`]: when the statement sets `hybrid_ed25519`, every verified
+/// signature must also carry a valid Ed25519 signature over `m` under its public key's
+/// `ed25519_key`, or the batch reports `all_valid = false`. The guest cannot check Ed25519, so a
+/// proof attests only the XMSS half; the commitment binds the flag and keys for this check.
+pub fn verify_batch_hybrid(batch: &VerificationBatch) -> (bool, u32) {
+    let (all_valid, count) = verify_batch(batch);
+    (all_valid && ed25519_signatures_valid(batch), count)
+}
+
+/// The Ed25519 half of [`verify_batch_hybrid`] on its own: `true` for non-hybrid statements,
+/// otherwise whether every verified signature has a valid Ed25519 signature under its key.
+pub fn ed25519_signatures_valid(batch: &VerificationBatch) -> bool {
+    if !batch.statement.hybrid_ed25519 {
+        return true;
+    }
+    let (Some((start, end)), Some(keys)) = (
+        signature_range(&batch.statement),
+        bound_public_keys(&batch.statement),
+    ) else {
+        return false;
+    };
+    batch
+        .witness
+        .signatures
+        .iter()
+        .zip(&keys[start..end])
+        .all(|(sig, pk)| {
+            ed25519_verifies(
+                pk.ed25519_key.as_deref(),
+                sig.ed25519_signature.as_deref(),
+                &batch.statement.m,
+            )
+        })
+}
+
+fn reuses_leaf_index(batch: &VerificationBatch) -> bool {
+    let (Some((start, end)), Some(keys)) = (
+        signature_range(&batch.statement),
+        bound_public_keys(&batch.statement),
+    ) else {
+        return false;
+    };
+    let signed: Vec<_> = batch
+        .witness
+        .signatures
+        .iter()
+        .zip(&keys[start..end])
+        .collect();
+    signed.iter().enumerate().any(|(i, (sig, pk))| {
+        signed[..i]
+            .iter()
+            .any(|(seen, seen_pk)| seen_pk.root == pk.root && seen.leaf_index == sig.leaf_index)
+    })
+}
+
+/// Verify a batch with hash-sig and report each signature's status. In
+/// [`VerifyMode::FailFast`], signatures after the first invalid one are
+/// [`SigStatus::NotChecked`]. Returns `None` for malformed batches, which `verify_batch`
+/// reports as `(false, 0)`.
+pub fn verify_batch_detailed(
+    batch: &VerificationBatch,
+    mode: VerifyMode,
+) -> Option<DetailedVerificationResult> {
+    let statement = &batch.statement;
+    let signatures = &batch.witness.signatures;
+    let (start, end, epoch) = batch_prechecks(batch)?;
+
+    let mut statuses = vec![SigStatus::NotChecked; signatures.len()];
+    for ((sig, pk), status) in signatures
+        .iter()
+        .zip(statement.public_keys[start..end].iter())
+        .zip(statuses.iter_mut())
+    {
+        if verify_one(sig, pk, &statement.m, epoch) {
+            *status = SigStatus::Valid;
+        } else {
+            *status = SigStatus::Invalid;
+            if mode == VerifyMode::FailFast {
+                break;
+            }
+        }
+    }
+    Some(DetailedVerificationResult { statuses })
+}
+
+/// [`verify_batch`] for an unranged, strict batch of `k` signatures whose signatures and public
+/// keys are fetched one at a time, so callers can stream them (e.g. from disk) instead of
+/// holding both `Vec`s in memory. `fetch(i)` returns the `i`th signature and its public key.
+///
+/// The checks match `verify_batch`: a malformed pair, or a key whose lengths differ from the
+/// first key's, makes the whole batch `(false, 0)` even if earlier signatures were verified.
+pub fn verify_batch_lazy<F>(
+    params: &TslParams,
+    k: u32,
+    m: &[u8],
+    ep: u64,
+    mut fetch: F,
+) -> (bool, u32)
+where
+    F: FnMut(usize) -> (Signature, PublicKey),
+{
+    if !params_match(params) {
+        return (false, 0);
+    }
+    let Ok(epoch) = u32::try_from(ep) else {
+        return (false, 0);
+    };
+    let tree_height = params.tree_height as usize;
+    let mut key_lengths = None;
+    let mut all_valid = true;
+    for index in 0..k as usize {
+        let (sig, pk) = fetch(index);
+        if auth_path_height(&sig) != tree_height {
+            return (false, 0);
+        }
+        let lengths = (pk.root.len(), pk.parameter.len());
+        if *key_lengths.get_or_insert(lengths) != lengths {
+            return (false, 0);
+        }
+        all_valid &= verify_one(&sig, &pk, m, epoch);
+    }
+    (all_valid, k)
+}
+
+/// Verify a series of signatures from one signer, the `i`th signing its message at epoch
+/// `start_epoch + i`. Since each epoch owns one leaf, this also rules out reused or skipped
+/// epochs: a signature presented out of order carries the wrong leaf index and fails. Any
+/// invalid signature, or an epoch beyond `u32`, makes the whole series `false`.
+pub fn verify_signer_series(
+    pk: &PublicKey,
+    start_epoch: u64,
+    series: &[(&[u8], Signature)],
+) -> bool {
+    series.iter().enumerate().all(|(offset, (message, sig))| {
+        start_epoch
+            .checked_add(offset as u64)
+            .and_then(|epoch| u32::try_from(epoch).ok())
+            .is_some_and(|epoch| {
+                auth_path_height(sig) == WINTERNITZ_TREE_HEIGHT
+                    && verify_one(sig, pk, message, epoch)
+            })
+    })
+}
+
+/// Verify every signature on the rayon pool, returning overall validity and the wall-clock
+/// time of the whole parallel region. Validity is an `all()` reduction, so it does not depend
+/// on thread scheduling even though it stops early on an invalid signature. Malformed batches
+/// are invalid.
+pub fn verify_parallel(batch: &VerificationBatch) -> (bool, Duration) {
+    let t0 = Instant::now();
+    let valid = match batch_prechecks(batch) {
+        Some((start, end, epoch)) => batch
+            .witness
+            .signatures
+            .par_iter()
+            .zip(batch.statement.public_keys[start..end].par_iter())
+            .all(|(sig, pk)| verify_one(sig, pk, &batch.statement.m, epoch)),
+        None => false,
+    };
+    (valid, t0.elapsed())
+}
+
+/// Verify many independent batches concurrently, one rayon task per batch, returning each
+/// batch's `(all_valid, count)` in the order of `batches`. Within a batch verification is
+/// sequential and stops at the first invalid signature, as in [`VerifyMode::FailFast`], so
+/// `count` is the number of signatures checked.
+///
+/// For an invalid batch that `count` stops short of the guest's, which checks every signature
+/// and reveals their total as [`verify_batch`] does; compare only `all_valid` with a proof.
+pub fn verify_batches_parallel(batches: &[VerificationBatch]) -> Vec<(bool, u32)> {
+    batches
+        .par_iter()
+        .map(|batch| summarize(batch, VerifyMode::FailFast))
+        .collect()
+}
+
+/// Batch-level checks shared by the verifiers: returns the signature range and epoch, or
+/// `None` if the batch is malformed.
+fn batch_prechecks(batch: &VerificationBatch) -> Option<(usize, usize, u32)> {
+    let statement = &batch.statement;
+    let signatures = &batch.witness.signatures;
+    let (start, end) = signature_range(statement)?;
+    // The range lies within the first `k` keys, so extra lenient-mode keys are never read.
+    bound_public_keys(statement)?;
+    if signatures.len() != end - start {
+        return None;
+    }
+    if !params_match(&batch.params) {
+        return None;
+    }
+    let tree_height = batch.params.tree_height as usize;
+    if signatures
+        .iter()
+        .any(|sig| auth_path_height(sig) != tree_height)
+    {
+        return None;
+    }
+    check_public_key_lengths(statement).ok()?;
+    if !epoch_in_window(statement) {
+        return None;
+    }
+    if let Some(expected) = statement.randomness_commitment {
+        if randomness_commitment(signatures) != expected {
+            return None;
+        }
+    }
+    let epoch = u32::try_from(statement.ep).ok()?;
+    Some((start, end, epoch))
+}
+
+/// Version byte that starts the commitment preimage; matches the guest's.
+pub const COMMITMENT_FORMAT_VERSION: u8 = 4;
+const TAG_K: u8 = 0x01;
+const TAG_EP: u8 = 0x02;
+const TAG_M: u8 = 0x03;
+const TAG_PUBKEYS: u8 = 0x04;
+const TAG_RANGE: u8 = 0x05;
+const TAG_EPOCH_WINDOW: u8 = 0x06;
+const TAG_RANDOMNESS: u8 = 0x07;
+const TAG_HYBRID: u8 = 0x08;
+
+/// Host mirror of the guest's statement commitment: SHA-256 over [`COMMITMENT_FORMAT_VERSION`]
+/// and the little-endian encoding of `k`, `ep`, the length-prefixed message, every public key's
+/// root, parameter, length-prefixed acceptable roots, and optional length-prefixed Ed25519 key,
+/// the optional signature range, the optional epoch window, the optional randomness commitment,
+/// and the hybrid flag, each field preceded by its own domain tag byte.
+pub fn statement_commitment(statement: &Statement) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update([COMMITMENT_FORMAT_VERSION]);
+    hasher.update([TAG_K]);
+    hasher.update(statement.k.to_le_bytes());
+    hasher.update([TAG_EP]);
+    hasher.update(statement.ep.to_le_bytes());
+    hasher.update([TAG_M]);
+    hasher.update((statement.m.len() as u32).to_le_bytes());
+    hasher.update(&statement.m);
+    hasher.update([TAG_PUBKEYS]);
+    let public_keys = bound_public_keys(statement).unwrap_or(&statement.public_keys);
+    hasher.update((public_keys.len() as u32).to_le_bytes());
+    for pk in public_keys {
+        hasher.update(&pk.root);
+        hasher.update(&pk.parameter);
+        hasher.update((pk.acceptable_roots.len() as u32).to_le_bytes());
+        for root in &pk.acceptable_roots {
+            hasher.update(root);
+        }
+        match &pk.ed25519_key {
+            None => hasher.update([0u8]),
+            Some(key) => {
+                hasher.update([1u8]);
+                hasher.update((key.len() as u32).to_le_bytes());
+                hasher.update(key);
+            }
+        }
+    }
+    hasher.update([TAG_RANGE]);
+    match statement.range {
+        None => hasher.update([0u8]),
+        Some((start, end)) => {
+            hasher.update([1u8]);
+            hasher.update(start.to_le_bytes());
+            hasher.update(end.to_le_bytes());
+        }
+    }
+    hasher.update([TAG_EPOCH_WINDOW]);
+    match statement.epoch_window {
+        None => hasher.update([0u8]),
+        Some((min_epoch, max_epoch)) => {
+            hasher.update([1u8]);
+            hasher.update(min_epoch.to_le_bytes());
+            hasher.update(max_epoch.to_le_bytes());
+        }
+    }
+    hasher.update([TAG_RANDOMNESS]);
+    match &statement.randomness_commitment {
+        None => hasher.update([0u8]),
+        Some(commitment) => {
+            hasher.update([1u8]);
+            hasher.update(commitment);
+        }
+    }
+    hasher.update([TAG_HYBRID]);
+    hasher.update([statement.hybrid_ed25519 as u8]);
+    hasher.finalize().into()
+}
+
+/// Host mirror of the guest's `randomness_commitment`: SHA-256 over each signature's randomness
+/// in order, each prefixed by its length as a little-endian `u32`.
+pub fn randomness_commitment(signatures: &[Signature]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    for sig in signatures {
+        hasher.update((sig.randomness.len() as u32).to_le_bytes());
+        hasher.update(&sig.randomness);
+    }
+    hasher.finalize().into()
+}
+
+/// Signature slice `[start, end)` a statement covers, as in the guest: `statement.range` or all
+/// `k` signatures. `None` if the range is reversed or extends past `k`.
+pub fn signature_range(statement: &Statement) -> Option<(usize, usize)> {
+    let (start, end) = statement.range.unwrap_or((0, statement.k));
+    if start > end || end > statement.k {
+        return None;
+    }
+    Some((start as usize, end as usize))
+}
+
+/// Host mirror of the guest's `epoch_in_window`: whether `statement.ep` lies in the inclusive
+/// `epoch_window`, if there is one.
+pub fn epoch_in_window(statement: &Statement) -> bool {
+    match statement.epoch_window {
+        Some((min_epoch, max_epoch)) => (min_epoch..=max_epoch).contains(&statement.ep),
+        None => true,
+    }
+}
+
+/// Host mirror of the guest's `bound_public_keys`: all public keys, or only the first `k` when
+/// `allow_extra_public_keys` is set. `None` if the key count does not fit the mode.
+pub fn bound_public_keys(statement: &Statement) -> Option<&[PublicKey]> {
+    let k = statement.k as usize;
+    let keys = &statement.public_keys;
+    if keys.len() == k || (statement.allow_extra_public_keys && keys.len() > k) {
+        Some(&keys[..k])
+    } else {
+        None
+    }
+}
+
+/// Host mirror of the guest's distinct-signer count: keys are distinct when their roots differ.
+pub fn distinct_signers(statement: &Statement) -> u32 {
+    let keys = bound_public_keys(statement).unwrap_or(&statement.public_keys);
+    keys.iter()
+        .enumerate()
+        .filter(|(i, pk)| keys[..*i].iter().all(|seen| seen.root != pk.root))
+        .count() as u32
+}
+
+fn params_match(params: &TslParams) -> bool {
+    params.w == 2
+        && params.v as usize == WINTERNITZ_W1_NUM_CHAINS
+        && params.tree_height as usize == WINTERNITZ_TREE_HEIGHT
+}
+
+fn auth_path_height(sig: &Signature) -> usize {
+    match &sig.auth_path_mask {
+        Some(mask) => mask.len(),
+        None => sig.auth_path.len(),
+    }
+}
+
+fn verify_one(sig: &Signature, pk: &PublicKey, message: &[u8], epoch: u32) -> bool {
+    if sig.leaf_index != epoch {
+        return false;
+    }
+    let Ok(digest) = <[u8; 32]>::try_from(message) else {
+        return false;
+    };
+    let Ok(sig) = expand_auth_path(sig, &pk.parameter).and_then(|sig| import_signature(&sig))
+    else {
+        return false;
+    };
+    // hash-sig only knows a single root, so each acceptable root is tried as its own key.
+    std::iter::once(&pk.root)
+        .chain(pk.acceptable_roots.iter())
+        .any(|root| {
+            let candidate = PublicKey {
+                root: root.clone(),
+                parameter: pk.parameter.clone(),
+                acceptable_roots: Vec::new(),
+                ed25519_key: None,
+            };
+            import_public_key(&candidate).is_ok_and(|candidate| {
+                SIGWinternitzLifetime18W1::verify(&candidate, epoch, &digest, &sig)
+            })
+        })
+}
+
+/// Strict Ed25519 verification of `signature` over `message` under `public_key`; missing or
+/// malformed keys and signatures do not verify.
+fn ed25519_verifies(public_key: Option<&[u8]>, signature: Option<&[u8]>, message: &[u8]) -> bool {
+    let Some(Ok(public_key)) = public_key.map(<[u8; 32]>::try_from) else {
+        return false;
+    };
+    let Ok(key) = ed25519_dalek::VerifyingKey::from_bytes(&public_key) else {
+        return false;
+    };
+    let Some(Ok(signature)) = signature.map(ed25519_dalek::Signature::from_slice) else {
+        return false;
+    };
+    key.verify_strict(message, &signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xmss_types::Witness;
+
+    fn node() -> Vec<u8> {
+        vec![0u8; NODE_BYTES]
+    }
+
+    fn structural_batch(k: usize) -> VerificationBatch {
+        let public_keys = (0..k)
+            .map(|_| PublicKey {
+                root: node(),
+                parameter: vec![0u8; PARAMETER_BYTES],
+                acceptable_roots: Vec::new(),
+                ed25519_key: None,
+            })
+            .collect();
+        let signatures = (0..k)
+            .map(|_| Signature {
+                leaf_index: 0,
+                randomness: vec![0u8; RANDOMNESS_BYTES],
+                wots_chain_ends: vec![node(); WINTERNITZ_W1_NUM_CHAINS],
+                auth_path: vec![node(); WINTERNITZ_TREE_HEIGHT],
+                auth_path_mask: None,
+                ed25519_signature: None,
+            })
+            .collect();
+        VerificationBatch {
+            params: TslParams {
+                w: 2,
+                v: WINTERNITZ_W1_NUM_CHAINS as u16,
+                d0: 0,
+                security_bits: 128,
+                tree_height: WINTERNITZ_TREE_HEIGHT as u16,
+            },
+            statement: Statement {
+                k: k as u32,
+                ep: 0,
+                m: vec![0u8; 32],
+                public_keys,
+                range: None,
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
+            },
+            witness: Witness { signatures },
+        }
+    }
+
+    #[test]
+    fn rejects_public_key_with_short_parameter() {
+        let mut batch = structural_batch(3);
+        batch.statement.public_keys[2].parameter.truncate(16);
+
+        let err = check_public_key_lengths(&batch.statement).unwrap_err();
+        assert!(matches!(
+            err,
+            BatchStructureError::PublicKeyLengthMismatch {
+                index: 2,
+                parameter_len: 16,
+                ..
+            }
+        ));
+        assert_eq!(verify_batch(&batch), (false, 0));
+    }
+
+    #[test]
+    fn consistent_public_keys_reach_signature_checks() {
+        let batch = structural_batch(2);
+        assert_eq!(check_public_key_lengths(&batch.statement), Ok(()));
+        assert_eq!(verify_batch(&batch), (false, 2));
+    }
+
+    fn structure_error(mutate: impl FnOnce(&mut VerificationBatch)) -> BatchStructureError {
+        let mut batch = structural_batch(3);
+        assert_eq!(validate_batch_structure(&batch), Ok(()));
+        mutate(&mut batch);
+        validate_batch_structure(&batch).unwrap_err()
+    }
+
+    #[test]
+    fn structure_rejects_unsupported_params() {
+        let err = structure_error(|b| b.params.v = 64);
+        assert!(matches!(
+            err,
+            BatchStructureError::UnsupportedParams { v: 64, .. }
+        ));
+    }
+
+    #[test]
+    fn structure_rejects_invalid_range() {
+        let err = structure_error(|b| b.statement.range = Some((2, 4)));
+        assert_eq!(
+            err,
+            BatchStructureError::InvalidRange {
+                start: 2,
+                end: 4,
+                k: 3
+            }
+        );
+    }
+
+    #[test]
+    fn structure_rejects_count_mismatches() {
+        let err = structure_error(|b| {
+            b.statement.public_keys.pop();
+        });
+        assert!(matches!(
+            err,
+            BatchStructureError::CountMismatch {
+                what: "public keys",
+                expected: 3,
+                actual: 2
+            }
+        ));
+        let err = structure_error(|b| b.statement.range = Some((1, 3)));
+        assert!(matches!(
+            err,
+            BatchStructureError::CountMismatch {
+                what: "signatures",
+                expected: 2,
+                actual: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn structure_allows_extra_public_keys_only_in_lenient_mode() {
+        let mut batch = structural_batch(3);
+        batch.statement.k = 2;
+        batch.witness.signatures.pop();
+        batch.statement.public_keys[2].root.clear();
+        assert!(matches!(
+            validate_batch_structure(&batch),
+            Err(BatchStructureError::CountMismatch {
+                what: "public keys",
+                ..
+            })
+        ));
+        batch.statement.allow_extra_public_keys = true;
+        assert_eq!(validate_batch_structure(&batch), Ok(()));
+        assert_eq!(bound_public_keys(&batch.statement).map(<[_]>::len), Some(2));
+    }
+
+    #[test]
+    fn structure_rejects_message_and_epoch() {
+        let err = structure_error(|b| b.statement.m.push(0));
+        assert_eq!(err, BatchStructureError::MessageLength { actual: 33 });
+        let err = structure_error(|b| b.statement.ep = 1 << WINTERNITZ_TREE_HEIGHT);
+        assert!(matches!(err, BatchStructureError::EpochOutOfRange { .. }));
+    }
+
+    #[test]
+    fn structure_rejects_bad_key_sizes() {
+        let err = structure_error(|b| b.statement.public_keys[1].root.truncate(8));
+        assert!(matches!(
+            err,
+            BatchStructureError::FieldLength {
+                what: "root",
+                index: 1,
+                ..
+            }
+        ));
+        let err =
+            structure_error(|b| b.statement.public_keys[2].acceptable_roots = vec![vec![0; 4]]);
+        assert!(matches!(
+            err,
+            BatchStructureError::FieldLength {
+                what: "acceptable root",
+                index: 2,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn structure_rejects_bad_signature_sizes() {
+        let err = structure_error(|b| b.witness.signatures[0].randomness.clear());
+        assert!(matches!(
+            err,
+            BatchStructureError::FieldLength {
+                what: "randomness",
+                index: 0,
+                ..
+            }
+        ));
+        let err = structure_error(|b| {
+            b.witness.signatures[1].wots_chain_ends.pop();
+        });
+        assert!(matches!(
+            err,
+            BatchStructureError::FieldLength {
+                what: "chain count",
+                index: 1,
+                ..
+            }
+        ));
+        let err = structure_error(|b| b.witness.signatures[1].wots_chain_ends[7] = vec![0; 3]);
+        assert!(matches!(
+            err,
+            BatchStructureError::FieldLength {
+                what: "chain end",
+                ..
+            }
+        ));
+        let err = structure_error(|b| {
+            b.witness.signatures[2].auth_path.pop();
+        });
+        assert!(matches!(
+            err,
+            BatchStructureError::FieldLength {
+                what: "auth path height",
+                index: 2,
+                ..
+            }
+        ));
+        let err = structure_error(|b| b.witness.signatures[2].auth_path[0] = Vec::new());
+        assert!(matches!(
+            err,
+            BatchStructureError::FieldLength {
+                what: "auth path node",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn structure_rejects_inconsistent_mask() {
+        let err = structure_error(|b| {
+            let mut mask = vec![true; WINTERNITZ_TREE_HEIGHT];
+            mask[4] = false;
+            b.witness.signatures[0].auth_path_mask = Some(mask);
+        });
+        assert_eq!(
+            err,
+            BatchStructureError::AuthPathMask {
+                index: 0,
+                present: WINTERNITZ_TREE_HEIGHT - 1,
+                nodes: WINTERNITZ_TREE_HEIGHT
+            }
+        );
+    }
+
+    #[test]
+    fn structure_rejects_leaf_index_mismatch() {
+        let err = structure_error(|b| b.witness.signatures[1].leaf_index = 5);
+        assert_eq!(
+            err,
+            BatchStructureError::LeafIndex {
+                index: 1,
+                leaf_index: 5,
+                ep: 0
+            }
+        );
+    }
+
+    /// End-to-end pin of the W1 instantiation used by host input generation: a real hash-sig
+    /// signature, exported with the Poseidon sizes, must verify as a one-signature batch.
+    #[test]
+    fn exported_w1_signature_verifies() {
+        use crate::hashsig_export::{export_public_key, export_signature};
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5EED_0001);
+        let epoch = 3u32;
+        let digest = crate::hash_message_to_digest(b"w1 reference vector");
+        let (pk, sk) = SIGWinternitzLifetime18W1::key_gen(&mut rng, 0, 8);
+        let sig = SIGWinternitzLifetime18W1::sign(&mut rng, &sk, epoch, &digest).unwrap();
+        let pk = export_public_key(&pk).unwrap();
+        let sig = export_signature(&sig).unwrap();
+
+        assert_eq!(pk.root.len(), NODE_BYTES);
+        assert_eq!(pk.parameter.len(), PARAMETER_BYTES);
+        assert_eq!(sig.randomness.len(), RANDOMNESS_BYTES);
+        assert_eq!(sig.chain_hashes.len(), WINTERNITZ_W1_NUM_CHAINS);
+        assert_eq!(sig.auth_path.len(), WINTERNITZ_TREE_HEIGHT);
+
+        let mut batch = structural_batch(1);
+        batch.statement.ep = epoch as u64;
+        batch.statement.m = digest.to_vec();
+        batch.statement.public_keys[0] = PublicKey {
+            root: pk.root,
+            parameter: pk.parameter,
+            acceptable_roots: Vec::new(),
+            ed25519_key: None,
+        };
+        batch.witness.signatures[0] = sig.into_xmss_signature(epoch);
+
+        assert_eq!(validate_batch_structure(&batch), Ok(()));
+        assert_eq!(verify_batch(&batch), (true, 1));
+    }
+
+    #[test]
+    fn masked_auth_paths_verify_like_full_ones() {
+        let mut batch = crate::testing::build_test_batch(41, 1);
+        assert_eq!(verify_batch(&batch), (true, 1));
+
+        batch.witness.signatures[0].auth_path_mask = Some(vec![true; WINTERNITZ_TREE_HEIGHT]);
+        assert_eq!(validate_batch_structure(&batch), Ok(()));
+        assert_eq!(verify_batch(&batch), (true, 1));
+
+        // Omitting a real sibling substitutes the default node, which no longer leads to the root.
+        let sig = &mut batch.witness.signatures[0];
+        sig.auth_path.remove(3);
+        sig.auth_path_mask.as_mut().unwrap()[3] = false;
+        assert_eq!(validate_batch_structure(&batch), Ok(()));
+        assert_eq!(verify_batch(&batch), (false, 1));
+    }
+
+    #[test]
+    fn fail_fast_leaves_later_signatures_unchecked() {
+        let mut batch = crate::testing::build_test_batch(7, 3);
+        batch.witness.signatures[0].randomness[0] ^= 1;
+
+        let fail_fast = verify_batch_detailed(&batch, VerifyMode::FailFast).unwrap();
+        assert_eq!(
+            fail_fast.statuses,
+            vec![
+                SigStatus::Invalid,
+                SigStatus::NotChecked,
+                SigStatus::NotChecked
+            ]
+        );
+        assert_eq!(fail_fast.summary().num_signatures_verified, 1);
+
+        let exhaustive = verify_batch_detailed(&batch, VerifyMode::Exhaustive).unwrap();
+        assert_eq!(
+            exhaustive.statuses,
+            vec![SigStatus::Invalid, SigStatus::Valid, SigStatus::Valid]
+        );
+        assert_eq!(verify_batch(&batch), (false, 3));
+    }
+
+    #[test]
+    fn tagged_commitment_differs_from_untagged_layout() {
+        use sha2::{Digest, Sha256};
+
+        let statement = structural_batch(1).statement;
+        let pk = &statement.public_keys[0];
+        // Format version 0: the same fields without the version byte and domain tags.
+        let mut untagged = Sha256::new();
+        untagged.update(statement.k.to_le_bytes());
+        untagged.update(statement.ep.to_le_bytes());
+        untagged.update((statement.m.len() as u32).to_le_bytes());
+        untagged.update(&statement.m);
+        untagged.update(1u32.to_le_bytes());
+        untagged.update(&pk.root);
+        untagged.update(&pk.parameter);
+        untagged.update(0u32.to_le_bytes());
+        untagged.update([0u8]);
+        let untagged: [u8; 32] = untagged.finalize().into();
+
+        assert_ne!(statement_commitment(&statement), untagged);
+    }
+
+    #[test]
+    fn randomness_commitment_binds_signature_randomness() {
+        let mut batch = crate::testing::build_test_batch(37, 2);
+        batch.statement.randomness_commitment =
+            Some(randomness_commitment(&batch.witness.signatures));
+        assert_eq!(verify_batch(&batch), (true, 2));
+
+        // A different but still valid signature for the same key would carry new randomness.
+        batch.witness.signatures[0].randomness[0] ^= 1;
+        assert_eq!(verify_batch(&batch), (false, 0));
+        assert!(!verify_parallel(&batch).0);
+    }
+
+    #[test]
+    fn hybrid_statements_require_both_signatures() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let mut batch = crate::testing::build_test_batch(43, 2);
+        let signing_keys = [
+            SigningKey::from_bytes(&[7u8; 32]),
+            SigningKey::from_bytes(&[8u8; 32]),
+        ];
+        for ((pk, sig), signing_key) in batch
+            .statement
+            .public_keys
+            .iter_mut()
+            .zip(&mut batch.witness.signatures)
+            .zip(&signing_keys)
+        {
+            pk.ed25519_key = Some(signing_key.verifying_key().to_bytes().to_vec());
+            sig.ed25519_signature = Some(signing_key.sign(&batch.statement.m).to_bytes().to_vec());
+        }
+        // The flag is committed to; once set, both schemes must pass.
+        let plain = statement_commitment(&batch.statement);
+        batch.statement.hybrid_ed25519 = true;
+        assert_ne!(statement_commitment(&batch.statement), plain);
+        assert_eq!(verify_batch_hybrid(&batch), (true, 2));
+
+        let mut wrong_message = batch.clone();
+        wrong_message.witness.signatures[1].ed25519_signature = Some(
+            signing_keys[1]
+                .sign(b"some other message")
+                .to_bytes()
+                .to_vec(),
+        );
+        assert_eq!(verify_batch_hybrid(&wrong_message), (false, 2));
+        // The guest-equivalent verifier does not see the Ed25519 half.
+        assert_eq!(verify_batch(&wrong_message), (true, 2));
+
+        let mut missing = batch.clone();
+        missing.witness.signatures[0].ed25519_signature = None;
+        assert_eq!(verify_batch_hybrid(&missing), (false, 2));
+
+        let mut swapped_key = batch.clone();
+        swapped_key.statement.public_keys[0].ed25519_key =
+            batch.statement.public_keys[1].ed25519_key.clone();
+        assert_ne!(
+            statement_commitment(&swapped_key.statement),
+            statement_commitment(&batch.statement)
+        );
+        assert_eq!(verify_batch_hybrid(&swapped_key), (false, 2));
+
+        let mut malformed = batch;
+        malformed.statement.public_keys[1].ed25519_key = Some(vec![0u8; 31]);
+        assert_eq!(verify_batch_hybrid(&malformed), (false, 2));
+    }
+
+    #[test]
+    fn signer_series_must_use_consecutive_epochs() {
+        use crate::hashsig_export::{export_public_key, export_signature};
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(47);
+        let (pk, sk) = SIGWinternitzLifetime18W1::key_gen(&mut rng, 0, 3);
+        let exported = export_public_key(&pk).unwrap();
+        let pk = PublicKey {
+            root: exported.root,
+            parameter: exported.parameter,
+            acceptable_roots: Vec::new(),
+            ed25519_key: None,
+        };
+        let messages: Vec<[u8; 32]> = (0..3u8)
+            .map(|i| crate::hash_message_to_digest(&[i]))
+            .collect();
+        let series: Vec<(&[u8], Signature)> = messages
+            .iter()
+            .enumerate()
+            .map(|(epoch, message)| {
+                let sig =
+                    SIGWinternitzLifetime18W1::sign(&mut rng, &sk, epoch as u32, message).unwrap();
+                let sig = export_signature(&sig).unwrap();
+                (&message[..], sig.into_xmss_signature(epoch as u32))
+            })
+            .collect();
+        assert!(verify_signer_series(&pk, 0, &series));
+
+        let mut swapped = series.clone();
+        swapped.swap(1, 2);
+        assert!(!verify_signer_series(&pk, 0, &swapped));
+        assert!(!verify_signer_series(&pk, 1, &series));
+        let reused = [series[0].clone(), series[0].clone()];
+        assert!(!verify_signer_series(&pk, 0, &reused));
+    }
+
+    #[test]
+    fn strict_mode_rejects_reused_one_time_keys() {
+        let mut batch = crate::testing::build_test_batch(31, 2);
+        assert_eq!(verify_batch_strict(&batch), (true, 2));
+
+        // The same key signing twice at the same leaf is still a valid signature each time.
+        batch.statement.public_keys[1] = batch.statement.public_keys[0].clone();
+        batch.witness.signatures[1] = batch.witness.signatures[0].clone();
+        assert_eq!(verify_batch(&batch), (true, 2));
+        assert_eq!(verify_batch_strict(&batch), (false, 0));
+    }
+
+    #[test]
+    fn lazy_verification_matches_verify_batch() {
+        let mut batch = crate::testing::build_test_batch(29, 3);
+        let lazy = |batch: &VerificationBatch| {
+            let statement = &batch.statement;
+            verify_batch_lazy(
+                &batch.params,
+                statement.k,
+                &statement.m,
+                statement.ep,
+                |i| {
+                    (
+                        batch.witness.signatures[i].clone(),
+                        statement.public_keys[i].clone(),
+                    )
+                },
+            )
+        };
+        assert_eq!(lazy(&batch), (true, 3));
+        assert_eq!(lazy(&batch), verify_batch(&batch));
+
+        batch.witness.signatures[1].randomness[0] ^= 1;
+        assert_eq!(lazy(&batch), (false, 3));
+        assert_eq!(lazy(&batch), verify_batch(&batch));
+
+        batch.statement.public_keys[2].parameter.pop();
+        assert_eq!(lazy(&batch), (false, 0));
+        assert_eq!(lazy(&batch), verify_batch(&batch));
+    }
+
+    #[test]
+    fn epoch_window_bounds_verification() {
+        let mut batch = crate::testing::build_test_batch(23, 2);
+        batch.statement.epoch_window = Some((0, 4));
+        assert_eq!(verify_batch(&batch), (true, 2));
+
+        batch.statement.epoch_window = Some((1, 4));
+        assert_eq!(verify_batch(&batch), (false, 0));
+        assert!(!verify_parallel(&batch).0);
+    }
+
+    #[test]
+    fn parallel_verification_result_is_stable() {
+        let batch = crate::testing::build_test_batch(21, 4);
+        assert!(verify_parallel(&batch).0);
+
+        let mut invalid = batch;
+        invalid.witness.signatures[2].randomness[0] ^= 1;
+        for _ in 0..100 {
+            assert!(!verify_parallel(&invalid).0);
+        }
+    }
+
+    #[test]
+    fn parallel_batches_match_sequential_verification() {
+        let valid = crate::testing::build_test_batch(23, 2);
+        let mut invalid = crate::testing::build_test_batch(24, 3);
+        invalid.witness.signatures[1].randomness[0] ^= 1;
+        let mut malformed = valid.clone();
+        malformed.witness.signatures.pop();
+        let batches = [valid, invalid, malformed];
+
+        let parallel = verify_batches_parallel(&batches);
+        assert_eq!(parallel, [(true, 2), (false, 2), (false, 0)]);
+        for (batch, &(all_valid, count)) in batches.iter().zip(&parallel) {
+            assert_eq!(all_valid, verify_batch(batch).0);
+            let checked = verify_batch_detailed(batch, VerifyMode::FailFast)
+                .map_or(0, |detailed| {
+                    detailed.summary().num_signatures_verified as u32
+                });
+            assert_eq!(count, checked);
+        }
+        // Fail-fast stops counting at the first invalid signature; the guest-style count does not.
+        assert_eq!(verify_batch(&batches[1]), (false, 3));
+        assert!(verify_batches_parallel(&[]).is_empty());
+    }
+
+    #[test]
+    fn canonicalized_batches_share_a_commitment() {
+        let mut batch = crate::testing::build_test_batch(11, 3);
+        let mut reordered = batch.clone();
+        reordered.statement.public_keys.reverse();
+        reordered.witness.signatures.reverse();
+        assert_ne!(
+            statement_commitment(&batch.statement),
+            statement_commitment(&reordered.statement)
+        );
+
+        assert!(batch.canonicalize());
+        assert!(reordered.canonicalize());
+        assert_eq!(
+            statement_commitment(&batch.statement),
+            statement_commitment(&reordered.statement)
+        );
+        assert_eq!(verify_batch(&reordered), (true, 3));
+
+        reordered.statement.range = Some((0, 1));
+        let before = reordered.clone();
+        assert!(!reordered.canonicalize());
+        assert_eq!(reordered, before);
+    }
+}
@@ -0,0 +1,76 @@
+//! Deterministic fixtures for tests and benches (enabled by the `testing` feature).
+
+use rand::SeedableRng;
+use xmss_types::{PublicKey, Statement, TslParams, VerificationBatch, Witness};
+
+use crate::hashsig_export::{
+    export_public_key, export_signature, WINTERNITZ_TREE_HEIGHT, WINTERNITZ_W1_NUM_CHAINS,
+};
+use crate::{hash_message_to_digest, SIGWinternitzLifetime18W1, SignatureScheme};
+
+/// Build a fully valid batch of `n` signatures over one message at epoch 0, each from a fresh
+/// key. The same `seed` always yields the same batch.
+pub fn build_test_batch(seed: u64, n: usize) -> VerificationBatch {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let digest = hash_message_to_digest(&seed.to_le_bytes());
+    let epoch = 0u32;
+
+    let mut public_keys = Vec::with_capacity(n);
+    let mut signatures = Vec::with_capacity(n);
+    for _ in 0..n {
+        let (pk, sk) = SIGWinternitzLifetime18W1::key_gen(&mut rng, epoch as usize, 1);
+        let sig = SIGWinternitzLifetime18W1::sign(&mut rng, &sk, epoch, &digest)
+            .expect("hash-sig signing should succeed for a fresh key");
+        let pk = export_public_key(&pk).expect("export public key");
+        let sig = export_signature(&sig).expect("export signature");
+        public_keys.push(PublicKey {
+            root: pk.root,
+            parameter: pk.parameter,
+            acceptable_roots: Vec::new(),
+            ed25519_key: None,
+        });
+        signatures.push(sig.into_xmss_signature(epoch));
+    }
+
+    VerificationBatch {
+        params: TslParams {
+            w: 2,
+            v: WINTERNITZ_W1_NUM_CHAINS as u16,
+            d0: 0,
+            security_bits: 128,
+            tree_height: WINTERNITZ_TREE_HEIGHT as u16,
+        },
+        statement: Statement {
+            k: n as u32,
+            ep: epoch as u64,
+            m: digest.to_vec(),
+            public_keys,
+            range: None,
+            allow_extra_public_keys: false,
+            epoch_window: None,
+            randomness_commitment: None,
+            hybrid_ed25519: false,
+        },
+        witness: Witness { signatures },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::{validate_batch_structure, verify_batch};
+
+    #[test]
+    fn same_seed_builds_identical_valid_batches() {
+        let a = build_test_batch(7, 2);
+        let b = build_test_batch(7, 2);
+        assert_eq!(
+            bincode::serialize(&a).unwrap(),
+            bincode::serialize(&b).unwrap()
+        );
+        assert_ne!(build_test_batch(8, 2), a);
+
+        assert_eq!(validate_batch_structure(&a), Ok(()));
+        assert_eq!(verify_batch(&a), (true, 2));
+    }
+}
@@ -1,5 +1,6 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg(not(feature = "std"))]
@@ -7,22 +8,29 @@ extern crate alloc;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Signature {
     pub leaf_index: u32,
     pub randomness: Vec<u8>, // Variable length to accommodate different hash-sig instantiations
     pub wots_chain_ends: Vec<Vec<u8>>, // Renamed from wots_signature to reflect chain end semantics
     pub auth_path: Vec<Vec<u8>>, // Variable length for different hash sizes (e.g., 7×4 bytes for Poseidon KoalaBear nodes)
+    pub auth_path_mask: Option<Vec<bool>>, // Per-level presence flags; `false` levels are omitted from auth_path and use the default node
+    pub ed25519_signature: Option<Vec<u8>>, // 64-byte Ed25519 signature over the same message, required when the statement is hybrid
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PublicKey {
     pub root: Vec<u8>, // Variable length for different hash sizes (e.g., 7×4 bytes for Poseidon KoalaBear nodes)
     pub parameter: Vec<u8>, // Renamed from seed to match hash-sig semantics (5×4 bytes for Poseidon KoalaBear parameters)
+    pub acceptable_roots: Vec<Vec<u8>>, // Extra roots also accepted for this signer (e.g. the previous key during rotation); usually empty
+    pub ed25519_key: Option<Vec<u8>>, // 32-byte Ed25519 verifying key (compressed Edwards point) for hybrid statements
 }
 
 // Statement/Witness separation to align with pqSNARK.md
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Statement {
     // Number of signers/signatures expected
     pub k: u32,
@@ -32,20 +40,131 @@ pub struct Statement {
     pub m: Vec<u8>,
     // Public keys corresponding to each signature
     pub public_keys: Vec<PublicKey>,
+    // Optional `[start, end)` slice of the k signatures this proof covers; `None` means all of
+    // them. The witness then carries only `end - start` signatures.
+    pub range: Option<(u32, u32)>,
+    // Accept more than `k` public keys and ignore (and leave uncommitted) everything after the
+    // first `k`, so producers can append keys that older verifiers skip. Strict by default.
+    pub allow_extra_public_keys: bool,
+    // Optional inclusive `[min_epoch, max_epoch]` window `ep` must fall in, e.g. around the
+    // verifier's current epoch to reject replayed statements. `None` accepts any epoch.
+    pub epoch_window: Option<(u64, u64)>,
+    // Optional SHA-256 over the witness signatures' randomness, in order and each prefixed by
+    // its length as a little-endian u32, so the randomness cannot be swapped after the
+    // statement is committed. Covers only the signatures this proof carries.
+    pub randomness_commitment: Option<[u8; 32]>,
+    // Hybrid deployments migrating from Ed25519: every verified signature must also carry an
+    // Ed25519 signature over `m` under its public key's `ed25519_key`. Bound into the
+    // commitment, but the guest cannot check Ed25519, so only the host's
+    // `verify_batch_hybrid` enforces it.
+    pub hybrid_ed25519: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Witness {
     pub signatures: Vec<Signature>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+impl Witness {
+    /// Total bytes of every signature's randomness, WOTS chain ends, and authentication path.
+    pub fn total_bytes(&self) -> usize {
+        self.signatures
+            .iter()
+            .map(|sig| {
+                sig.randomness.len()
+                    + sig.wots_chain_ends.iter().map(Vec::len).sum::<usize>()
+                    + sig.auth_path.iter().map(Vec::len).sum::<usize>()
+            })
+            .sum()
+    }
+
+    /// [`Witness::total_bytes`] as the guest reveals it: one word, saturating at `u32::MAX`.
+    pub fn revealed_bytes(&self) -> u32 {
+        u32::try_from(self.total_bytes()).unwrap_or(u32::MAX)
+    }
+
+    /// Number of signatures byte-identical to an earlier one, or `None` if all are distinct.
+    /// Advisory only: duplicates usually mean a copy-paste bug in the batch producer.
+    pub fn duplicate_signatures(&self) -> Option<usize> {
+        let duplicates = self
+            .signatures
+            .iter()
+            .enumerate()
+            .filter(|(i, sig)| self.signatures[..*i].contains(sig))
+            .count();
+        (duplicates > 0).then_some(duplicates)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct VerificationResult {
     pub all_signatures_valid: bool,
     pub num_signatures_verified: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+impl VerificationResult {
+    /// Flag bit set in the compact status byte when every signature verified.
+    pub const COMPACT_ALL_VALID: u8 = 0b0000_0001;
+    /// Status-byte bits reserved for future flags; they must be zero for now.
+    pub const COMPACT_RESERVED: u8 = !Self::COMPACT_ALL_VALID;
+
+    /// Encode as a status byte plus count. Counts above `u32::MAX` saturate.
+    pub fn to_compact(&self) -> (u8, u32) {
+        let flags = if self.all_signatures_valid {
+            Self::COMPACT_ALL_VALID
+        } else {
+            0
+        };
+        let count = u32::try_from(self.num_signatures_verified).unwrap_or(u32::MAX);
+        (flags, count)
+    }
+
+    /// Decode a compact status. Returns `None` if any reserved flag bit is set.
+    pub fn from_compact(flags: u8, count: u32) -> Option<Self> {
+        if flags & Self::COMPACT_RESERVED != 0 {
+            return None;
+        }
+        Some(Self {
+            all_signatures_valid: flags & Self::COMPACT_ALL_VALID != 0,
+            num_signatures_verified: count as usize,
+        })
+    }
+}
+
+/// Outcome for one signature. `NotChecked` means verification stopped before reaching it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SigStatus {
+    Valid,
+    Invalid,
+    NotChecked,
+}
+
+/// Per-signature verification outcome, in witness order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DetailedVerificationResult {
+    pub statuses: Vec<SigStatus>,
+}
+
+impl DetailedVerificationResult {
+    /// Collapse to the guest's summary: every signature valid, and how many were checked.
+    pub fn summary(&self) -> VerificationResult {
+        VerificationResult {
+            all_signatures_valid: self.statuses.iter().all(|s| *s == SigStatus::Valid),
+            num_signatures_verified: self
+                .statuses
+                .iter()
+                .filter(|s| **s != SigStatus::NotChecked)
+                .count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TslParams {
     pub w: u16,
     pub v: u16,
@@ -54,20 +173,226 @@ pub struct TslParams {
     pub tree_height: u16,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct VerificationBatch {
     pub params: TslParams,
     pub statement: Statement,
     pub witness: Witness,
 }
 
+/// A batch whose `statement.k` exceeds the caller's limit; see [`VerificationBatch::with_max_k`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchTooLarge {
+    pub k: u32,
+    pub max: u32,
+}
+
+impl core::fmt::Display for BatchTooLarge {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "batch has k={} signers, above the limit of {}",
+            self.k, self.max
+        )
+    }
+}
+
+impl VerificationBatch {
+    /// Pass the batch through only if `statement.k` is at most `max`, so services accepting
+    /// untrusted batches can bound their size before doing any work. The struct itself stays
+    /// unconstrained.
+    pub fn with_max_k(self, max: u32) -> Result<Self, BatchTooLarge> {
+        match self.statement.k {
+            k if k > max => Err(BatchTooLarge { k, max }),
+            _ => Ok(self),
+        }
+    }
+
+    /// Sort public keys by root (then parameter and acceptable roots), moving each signature
+    /// with its key, so reordered signers yield the same statement commitment. Opt-in: the
+    /// caller decides when order should not matter.
+    ///
+    /// Returns `false` and leaves the batch unchanged when `range` is set or the witness is
+    /// not aligned one-to-one with the public keys, since reordering would then change which
+    /// keys the signatures cover.
+    pub fn canonicalize(&mut self) -> bool {
+        if self.statement.range.is_some()
+            || self.statement.public_keys.len() != self.witness.signatures.len()
+        {
+            return false;
+        }
+        let mut pairs: Vec<(PublicKey, Signature)> =
+            core::mem::take(&mut self.statement.public_keys)
+                .into_iter()
+                .zip(core::mem::take(&mut self.witness.signatures))
+                .collect();
+        pairs.sort_by(|(a, _), (b, _)| {
+            (&a.root, &a.parameter, &a.acceptable_roots).cmp(&(
+                &b.root,
+                &b.parameter,
+                &b.acceptable_roots,
+            ))
+        });
+        (self.statement.public_keys, self.witness.signatures) = pairs.into_iter().unzip();
+        true
+    }
+}
+
+/// Field-element widths of the Poseidon instantiation. The guest sizes its arrays from
+/// [`PoseidonConfig::DEFAULT`] and the host derives its export byte sizes from it, so trying
+/// another width is a one-value change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoseidonConfig {
+    /// Field elements per hash domain element (Merkle node, chain end).
+    pub hash_len_fe: usize,
+    /// Field elements in the public parameter.
+    pub parameter_len_fe: usize,
+    /// Field elements in the Winternitz randomness (rho).
+    pub randomness_len_fe: usize,
+}
+
+impl PoseidonConfig {
+    /// Bytes per encoded KoalaBear field element.
+    pub const FE_BYTES: usize = 4;
+
+    /// Widths of the hash-sig instantiation the host exports and the guest verifies.
+    pub const DEFAULT: PoseidonConfig = PoseidonConfig {
+        hash_len_fe: 7,
+        parameter_len_fe: 5,
+        randomness_len_fe: 5,
+    };
+
+    /// Bytes of one encoded node.
+    pub const fn node_bytes(&self) -> usize {
+        self.hash_len_fe * Self::FE_BYTES
+    }
+
+    /// Bytes of an encoded public parameter.
+    pub const fn parameter_bytes(&self) -> usize {
+        self.parameter_len_fe * Self::FE_BYTES
+    }
+
+    /// Bytes of encoded signature randomness.
+    pub const fn randomness_bytes(&self) -> usize {
+        self.randomness_len_fe * Self::FE_BYTES
+    }
+
+    /// Payload bytes of a signature with `num_chains` chain ends and a full auth path of
+    /// `tree_height` nodes: randomness, chain ends and auth path.
+    pub const fn signature_bytes(&self, num_chains: usize, tree_height: usize) -> usize {
+        self.randomness_bytes() + (num_chains + tree_height) * self.node_bytes()
+    }
+}
+
+impl Default for PoseidonConfig {
+    fn default() -> Self {
+        PoseidonConfig::DEFAULT
+    }
+}
+
+/// Public-output word indices the guest reveals, shared with host-side parsers so both agree on
+/// each word's meaning. Multi-word values start at their index, low word first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RevealLayout {
+    /// `1` if every checked signature verified, else `0`.
+    pub all_valid: usize,
+    /// Number of signatures checked.
+    pub count: usize,
+    /// First of [`RevealLayout::COMMITMENT_WORDS`] words holding the statement commitment.
+    pub commitment: usize,
+    /// Number of distinct signer roots among the statement's public keys.
+    pub distinct_signers: usize,
+    /// Start of the verified signature range.
+    pub range_start: usize,
+    /// End (exclusive) of the verified signature range.
+    pub range_end: usize,
+    /// First of [`RevealLayout::EPOCH_WINDOW_WORDS`] words: the minimum, then the maximum
+    /// accepted epoch, each as two words.
+    pub epoch_window: usize,
+    /// Total witness bytes processed, per [`Witness::revealed_bytes`].
+    pub witness_bytes: usize,
+    /// Poseidon hash-call count, revealed only by `profiling` guest builds.
+    pub hash_calls: usize,
+    /// Number of words a guest built without `profiling` reveals.
+    pub words: usize,
+}
+
+impl RevealLayout {
+    pub const COMMITMENT_WORDS: usize = 8;
+    pub const EPOCH_WINDOW_WORDS: usize = 4;
+}
+
+/// The layout the current guest reveals.
+pub const REVEAL_LAYOUT: RevealLayout = RevealLayout {
+    all_valid: 0,
+    count: 1,
+    commitment: 2,
+    distinct_signers: 10,
+    range_start: 11,
+    range_end: 12,
+    epoch_window: 13,
+    witness_bytes: 17,
+    hash_calls: 18,
+    words: 18,
+};
+
+/// Multi-line summary of the statement shape, parameters, and per-signature node counts,
+/// without any key or signature bytes.
+#[cfg(feature = "std")]
+impl std::fmt::Display for VerificationBatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params = &self.params;
+        let statement = &self.statement;
+        writeln!(
+            f,
+            "k={}, ep={}, message {} bytes, {} public keys, {} signatures",
+            statement.k,
+            statement.ep,
+            statement.m.len(),
+            statement.public_keys.len(),
+            self.witness.signatures.len()
+        )?;
+        if let Some((start, end)) = statement.range {
+            writeln!(f, "range [{start}, {end})")?;
+        }
+        if let Some((min_epoch, max_epoch)) = statement.epoch_window {
+            writeln!(f, "epoch window [{min_epoch}, {max_epoch}]")?;
+        }
+        write!(
+            f,
+            "params: w={}, v={}, d0={}, security_bits={}, tree_height={}",
+            params.w, params.v, params.d0, params.security_bits, params.tree_height
+        )?;
+        for (index, sig) in self.witness.signatures.iter().enumerate() {
+            write!(
+                f,
+                "\nsignature {index}: leaf {}, {} chain ends, {} auth path nodes",
+                sig.leaf_index,
+                sig.wots_chain_ends.len(),
+                sig.auth_path.len()
+            )?;
+            if let Some(mask) = &sig.auth_path_mask {
+                write!(
+                    f,
+                    " ({} of {} levels sent)",
+                    sig.auth_path.len(),
+                    mask.len()
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
 
-    #[test]
-    fn verification_batch_round_trips() {
-        let batch = VerificationBatch {
+    fn sample_batch() -> VerificationBatch {
+        VerificationBatch {
             params: TslParams {
                 w: 4,
                 v: 8,
@@ -83,12 +408,21 @@ mod tests {
                     PublicKey {
                         root: vec![1u8; 28],
                         parameter: vec![2u8; 20],
+                        acceptable_roots: Vec::new(),
+                        ed25519_key: None,
                     },
                     PublicKey {
                         root: vec![9u8; 28],
                         parameter: vec![8u8; 20],
+                        acceptable_roots: vec![vec![1u8; 28]],
+                        ed25519_key: None,
                     },
                 ],
+                range: None,
+                allow_extra_public_keys: false,
+                epoch_window: None,
+                randomness_commitment: None,
+                hybrid_ed25519: false,
             },
             witness: Witness {
                 signatures: vec![
@@ -97,20 +431,164 @@ mod tests {
                         randomness: vec![3u8; 20],
                         wots_chain_ends: vec![vec![4u8; 28]; 8],
                         auth_path: vec![vec![5u8; 28]; 10],
+                        auth_path_mask: None,
+                        ed25519_signature: None,
                     },
                     Signature {
                         leaf_index: 1,
                         randomness: vec![6u8; 20],
                         wots_chain_ends: vec![vec![7u8; 28]; 8],
-                        auth_path: vec![vec![8u8; 28]; 10],
+                        auth_path: vec![vec![8u8; 28]; 9],
+                        auth_path_mask: Some(
+                            (0..10).map(|level| level != 3).collect::<Vec<bool>>(),
+                        ),
+                        ed25519_signature: None,
                     },
                 ],
             },
-        };
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn verification_batch_round_trips() {
+        let batch = sample_batch();
 
         let json = serde_json::to_string(&batch).expect("serialize VerificationBatch");
         let decoded: VerificationBatch =
             serde_json::from_str(&json).expect("deserialize VerificationBatch");
         assert_eq!(decoded, batch);
     }
+
+    #[test]
+    fn verification_result_compact_round_trips() {
+        for (all_signatures_valid, num_signatures_verified) in [(true, 0), (false, 3), (true, 1000)]
+        {
+            let result = VerificationResult {
+                all_signatures_valid,
+                num_signatures_verified,
+            };
+            let (flags, count) = result.to_compact();
+            assert_eq!(VerificationResult::from_compact(flags, count), Some(result));
+        }
+        assert_eq!(
+            VerificationResult {
+                all_signatures_valid: true,
+                num_signatures_verified: 2,
+            }
+            .to_compact(),
+            (1, 2)
+        );
+    }
+
+    #[test]
+    fn witness_total_bytes_sums_signature_fields() {
+        // 20 + 8 * 28 + 10 * 28 and 20 + 8 * 28 + 9 * 28
+        assert_eq!(sample_batch().witness.total_bytes(), 524 + 496);
+        assert_eq!(sample_batch().witness.revealed_bytes(), 524 + 496);
+        assert_eq!(Witness { signatures: vec![] }.total_bytes(), 0);
+    }
+
+    #[test]
+    fn identical_signatures_are_counted_as_duplicates() {
+        let mut witness = sample_batch().witness;
+        assert_eq!(witness.duplicate_signatures(), None);
+        let first = witness.signatures[0].clone();
+        witness.signatures.extend([first.clone(), first]);
+        // Three copies of the first signature plus the distinct second one.
+        assert_eq!(witness.duplicate_signatures(), Some(2));
+    }
+
+    #[test]
+    fn max_k_bounds_the_batch_size() {
+        let batch = sample_batch();
+        assert_eq!(batch.clone().with_max_k(2), Ok(batch.clone()));
+        assert_eq!(batch.clone().with_max_k(u32::MAX), Ok(batch.clone()));
+        assert_eq!(batch.with_max_k(1), Err(BatchTooLarge { k: 2, max: 1 }));
+    }
+
+    #[test]
+    fn reveal_layout_assigns_every_word_once() {
+        let layout = REVEAL_LAYOUT;
+        let mut owners = [0u8; REVEAL_LAYOUT.words];
+        let mut claim = |start: usize, len: usize| {
+            for word in &mut owners[start..start + len] {
+                *word += 1;
+            }
+        };
+        claim(layout.all_valid, 1);
+        claim(layout.count, 1);
+        claim(layout.commitment, RevealLayout::COMMITMENT_WORDS);
+        claim(layout.distinct_signers, 1);
+        claim(layout.range_start, 1);
+        claim(layout.range_end, 1);
+        claim(layout.epoch_window, RevealLayout::EPOCH_WINDOW_WORDS);
+        claim(layout.witness_bytes, 1);
+        assert!(owners.iter().all(|&n| n == 1));
+        // The profiling word is appended after the default output.
+        assert_eq!(layout.hash_calls, layout.words);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn display_summarizes_without_raw_bytes() {
+        let summary = sample_batch().to_string();
+        assert!(summary.contains("2 signatures"));
+        assert!(summary.contains("tree_height=10"));
+        assert!(summary.contains("signature 1: leaf 1, 8 chain ends, 9 auth path nodes"));
+        assert!(!summary.contains("[6, 6"));
+    }
+
+    #[test]
+    fn detailed_result_summary_ignores_unchecked_signatures() {
+        let detailed = DetailedVerificationResult {
+            statuses: vec![SigStatus::Valid, SigStatus::Invalid, SigStatus::NotChecked],
+        };
+        assert_eq!(
+            detailed.summary(),
+            VerificationResult {
+                all_signatures_valid: false,
+                num_signatures_verified: 2,
+            }
+        );
+        let unchecked = DetailedVerificationResult {
+            statuses: vec![SigStatus::Valid, SigStatus::NotChecked],
+        };
+        assert!(!unchecked.summary().all_signatures_valid);
+    }
+
+    #[test]
+    fn compact_status_rejects_reserved_bits() {
+        assert_eq!(VerificationResult::from_compact(0b10, 1), None);
+        assert_eq!(VerificationResult::from_compact(0x81, 1), None);
+    }
+
+    #[test]
+    fn wider_config_scales_every_node_size() {
+        let wide = PoseidonConfig {
+            hash_len_fe: 8,
+            ..PoseidonConfig::DEFAULT
+        };
+        let default = PoseidonConfig::DEFAULT;
+        let fe = PoseidonConfig::FE_BYTES;
+        assert_eq!(wide.node_bytes(), 8 * fe);
+        assert_eq!(wide.node_bytes() - default.node_bytes(), fe);
+        assert_eq!(wide.parameter_bytes(), default.parameter_bytes());
+        assert_eq!(wide.randomness_bytes(), default.randomness_bytes());
+        assert_eq!(
+            wide.signature_bytes(163, 18) - default.signature_bytes(163, 18),
+            (163 + 18) * fe
+        );
+    }
+
+    // Built with `--no-default-features`: the plain types must stay usable when serde is off.
+    #[cfg(not(feature = "serde"))]
+    #[test]
+    fn types_are_usable_without_serde() {
+        let batch = sample_batch();
+        let copy = batch.clone();
+        assert_eq!(copy, batch);
+        assert_eq!(copy.statement.public_keys.len(), copy.statement.k as usize);
+        assert_eq!(copy.witness.signatures[1].leaf_index, 1);
+    }
 }